@@ -0,0 +1,395 @@
+//! Headless balance simulator.
+//!
+//! Plays many seeded runs per difficulty with a greedy bot and reports how
+//! far they got, what killed them, gold earned and the level reached.
+//!
+//! ```text
+//! crablo-sim [--runs N] [--seed HEX] [--difficulty easy|normal|hard]...
+//!            [--format csv|json] [--per-run] [--max-minutes M]
+//!            [--monsters monsters.toml]
+//! ```
+//!
+//! Seeds are hex, as the game shows them, so a run from the Hall of Fame can
+//! be replayed here and a seed from the report typed into the game.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::exit;
+
+use serde::Serialize;
+
+use crablo::core::command::PlayerCommand;
+use crablo::core::constants::{Difficulty, SIM_DT};
+use crablo::core::rng::{format_seed, parse_seed};
+use crablo::core::traits::Damageable;
+use crablo::core::Game;
+use crablo::systems::pathfinding::dist;
+use crablo::world::bestiary::{self, Bestiary};
+use crablo::world::entities::ShopItemType;
+
+/// Seconds between melee swings, roughly the pace of a player clicking.
+const BOT_ATTACK_INTERVAL: f32 = 0.25;
+
+struct Options {
+    runs: u32,
+    seed: u64,
+    difficulties: Vec<Difficulty>,
+    json: bool,
+    per_run: bool,
+    max_frames: u32,
+    monsters: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct RunResult {
+    difficulty: &'static str,
+    seed: String,
+    floor: i32,
+    level: i32,
+    gold: i32,
+    kills: i32,
+    score: i32,
+    cause: String,
+    frames: u32,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    difficulty: &'static str,
+    runs: u32,
+    avg_floor: f32,
+    avg_gold: f32,
+    floors: BTreeMap<i32, u32>,
+    levels: BTreeMap<i32, u32>,
+    causes: BTreeMap<String, u32>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    summaries: &'a [Summary],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runs: Option<&'a [RunResult]>,
+}
+
+/// Greedy bot: heal when low, fight whatever is nearest, grab nearby gold,
+/// visit the shop once per floor and buy whatever it offers in a fixed
+/// priority order, then take the stairs once the floor is cleared.
+struct Bot {
+    attack_timer: f32,
+}
+
+impl Bot {
+    fn new() -> Self {
+        Bot { attack_timer: 0. }
+    }
+
+    fn think(&mut self, game: &Game) -> Vec<PlayerCommand> {
+        let mut commands = vec![];
+        self.attack_timer -= SIM_DT;
+
+        if game.in_shop {
+            let priority = [
+                ShopItemType::Damage,
+                ShopItemType::Armor,
+                ShopItemType::MaxHp,
+                ShopItemType::Heal,
+            ];
+            for item_type in priority {
+                if let Some(i) = game.shop_items.iter().position(|item| {
+                    item.item_type == item_type && !item.purchased && item.cost <= game.player.gold
+                }) {
+                    commands.push(PlayerCommand::ShopBuy(i));
+                    return commands;
+                }
+            }
+            commands.push(PlayerCommand::ShopLeave);
+            return commands;
+        }
+
+        let player = &game.player;
+        let here = (player.x, player.y);
+
+        if player.hp() * 10 < player.max_hp() * 4 && player.heal_cd <= 0. {
+            commands.push(PlayerCommand::Heal);
+        }
+
+        let adjacent = game
+            .monsters
+            .iter()
+            .filter(|m| {
+                let dx = (m.x as i32 - here.0 as i32).abs();
+                let dy = (m.y as i32 - here.1 as i32).abs();
+                dx <= 1 && dy <= 1
+            })
+            .count();
+        if adjacent >= 2 && player.area_cd <= 0. {
+            commands.push(PlayerCommand::Area);
+        }
+        if player.ranged_cd <= 0. {
+            commands.push(PlayerCommand::Ranged);
+        }
+
+        let nearest_monster = game
+            .monsters
+            .iter()
+            .min_by_key(|m| dist(here, (m.x, m.y)))
+            .map(|m| (m.x, m.y));
+
+        if let Some(target) = nearest_monster {
+            let dx = (target.0 as i32 - here.0 as i32).abs();
+            let dy = (target.1 as i32 - here.1 as i32).abs();
+            if dx <= 1 && dy <= 1 {
+                if self.attack_timer <= 0. {
+                    self.attack_timer = BOT_ATTACK_INTERVAL;
+                    commands.push(PlayerCommand::Attack {
+                        x: target.0,
+                        y: target.1,
+                    });
+                }
+                return commands;
+            }
+        }
+
+        let cleared = game.monsters.is_empty() && game.gold.is_empty();
+        if cleared && game.can_descend() {
+            commands.push(PlayerCommand::Descend);
+            return commands;
+        }
+
+        // Walk somewhere once the previous path is used up
+        if player.path.is_empty() {
+            let nearest_gold = game.gold.iter().min_by_key(|&&g| dist(here, g)).copied();
+            let shop = game
+                .shop_room()
+                .filter(|_| game.shop_items.is_empty())
+                .map(|room| room.center());
+            let goal = match (nearest_gold, nearest_monster) {
+                _ if shop.is_some() => shop,
+                (Some(g), Some(m)) if dist(here, g) < dist(here, m) => Some(g),
+                (_, Some(m)) => Some(m),
+                (g, None) => g.or(game.map.stairs()),
+            };
+            if let Some((x, y)) = goal {
+                commands.push(PlayerCommand::PathTo { x, y });
+            }
+        }
+
+        commands
+    }
+}
+
+fn simulate(difficulty: Difficulty, seed: u64, max_frames: u32) -> RunResult {
+    let mut game = Game::with_seed(difficulty, seed);
+    let mut bot = Bot::new();
+    let mut frames = 0;
+
+    while frames < max_frames {
+        let commands = bot.think(&game);
+        frames += 1;
+        if game.step(&commands, SIM_DT).game_over {
+            break;
+        }
+    }
+
+    let cause = match game.death_cause {
+        _ if game.won => "Victory".to_string(),
+        Some(cause) => cause.name().to_string(),
+        None if game.player.is_dead() => "Unknown".to_string(),
+        None => "Timeout".to_string(),
+    };
+
+    RunResult {
+        difficulty: difficulty.name(),
+        seed: format_seed(seed),
+        floor: game.floor,
+        level: game.player.level,
+        gold: game.total_gold,
+        kills: game.total_kills,
+        score: game.score,
+        cause,
+        frames,
+    }
+}
+
+fn summarize(difficulty: Difficulty, results: &[RunResult]) -> Summary {
+    let mut summary = Summary {
+        difficulty: difficulty.name(),
+        runs: results.len() as u32,
+        avg_floor: 0.,
+        avg_gold: 0.,
+        floors: BTreeMap::new(),
+        levels: BTreeMap::new(),
+        causes: BTreeMap::new(),
+    };
+    for result in results {
+        *summary.floors.entry(result.floor).or_default() += 1;
+        *summary.levels.entry(result.level).or_default() += 1;
+        *summary.causes.entry(result.cause.clone()).or_default() += 1;
+        summary.avg_floor += result.floor as f32;
+        summary.avg_gold += result.gold as f32;
+    }
+    if !results.is_empty() {
+        summary.avg_floor /= results.len() as f32;
+        summary.avg_gold /= results.len() as f32;
+    }
+    summary
+}
+
+fn print_csv(summaries: &[Summary], runs: Option<&[RunResult]>) {
+    println!("difficulty,metric,key,value");
+    for s in summaries {
+        println!("{},runs,,{}", s.difficulty, s.runs);
+        println!("{},avg_floor,,{:.2}", s.difficulty, s.avg_floor);
+        println!("{},avg_gold,,{:.2}", s.difficulty, s.avg_gold);
+        for (floor, count) in &s.floors {
+            println!("{},floor,{},{}", s.difficulty, floor, count);
+        }
+        for (level, count) in &s.levels {
+            println!("{},level,{},{}", s.difficulty, level, count);
+        }
+        for (cause, count) in &s.causes {
+            println!("{},cause,{},{}", s.difficulty, csv_field(cause), count);
+        }
+    }
+
+    if let Some(runs) = runs {
+        println!();
+        println!("difficulty,seed,floor,level,gold,kills,score,cause,frames");
+        for r in runs {
+            println!(
+                "{},{},{},{},{},{},{},{},{}",
+                r.difficulty,
+                r.seed,
+                r.floor,
+                r.level,
+                r.gold,
+                r.kills,
+                r.score,
+                csv_field(&r.cause),
+                r.frames
+            );
+        }
+    }
+}
+
+/// Quote `text` for CSV when it holds a separator, quote or line break.
+/// Death causes are bestiary names, which can be anything.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn parse_difficulty(name: &str) -> Option<Difficulty> {
+    match name.to_ascii_lowercase().as_str() {
+        "easy" => Some(Difficulty::Easy),
+        "normal" => Some(Difficulty::Normal),
+        "hard" => Some(Difficulty::Hard),
+        _ => None,
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        runs: 1000,
+        seed: 1,
+        difficulties: vec![],
+        json: false,
+        per_run: false,
+        max_frames: (30. * 60. / SIM_DT) as u32,
+        monsters: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--runs" => {
+                options.runs = value("--runs")?.parse().map_err(|e| format!("{}", e))?;
+            }
+            "--seed" => {
+                let text = value("--seed")?;
+                options.seed = parse_seed(&text).ok_or(format!("seed {} is not hex", text))?;
+            }
+            "--difficulty" => {
+                let name = value("--difficulty")?;
+                let difficulty =
+                    parse_difficulty(&name).ok_or(format!("unknown difficulty {}", name))?;
+                options.difficulties.push(difficulty);
+            }
+            "--format" => match value("--format")?.as_str() {
+                "csv" => options.json = false,
+                "json" => options.json = true,
+                other => return Err(format!("unknown format {}", other)),
+            },
+            "--per-run" => options.per_run = true,
+            "--max-minutes" => {
+                let minutes: f32 = value("--max-minutes")?
+                    .parse()
+                    .map_err(|e| format!("{}", e))?;
+                options.max_frames = (minutes * 60. / SIM_DT) as u32;
+            }
+            "--monsters" => options.monsters = Some(PathBuf::from(value("--monsters")?)),
+            other => return Err(format!("unknown argument {}", other)),
+        }
+    }
+
+    if options.difficulties.is_empty() {
+        options.difficulties = vec![Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+    }
+    Ok(options)
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("crablo-sim: {}", e);
+            exit(2);
+        }
+    };
+
+    if let Some(path) = &options.monsters {
+        let roster = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Bestiary::from_toml(&text));
+        match roster {
+            Ok(roster) => {
+                bestiary::install(roster);
+            }
+            Err(e) => {
+                eprintln!("crablo-sim: {}: {}", path.display(), e);
+                exit(2);
+            }
+        }
+    }
+
+    let mut summaries = vec![];
+    let mut all_runs = vec![];
+    for &difficulty in &options.difficulties {
+        let results: Vec<RunResult> = (0..options.runs as u64)
+            .map(|i| simulate(difficulty, options.seed.wrapping_add(i), options.max_frames))
+            .collect();
+        summaries.push(summarize(difficulty, &results));
+        all_runs.extend(results);
+    }
+
+    let runs = options.per_run.then_some(all_runs.as_slice());
+    if options.json {
+        let report = Report {
+            summaries: &summaries,
+            runs,
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("crablo-sim: {}", e);
+                exit(1);
+            }
+        }
+    } else {
+        print_csv(&summaries, runs);
+    }
+}
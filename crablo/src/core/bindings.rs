@@ -0,0 +1,277 @@
+//! Input actions and the keys bound to them.
+//!
+//! Every control the player can rebind is an [`Action`]. [`Bindings`] maps
+//! each one to up to two keys and never lets one key trigger two actions.
+//! Keys are saved by name, so the settings file stays readable.
+
+use std::collections::BTreeMap;
+
+use macroquad::input::KeyCode;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Keys per action.
+pub const KEYS_PER_ACTION: usize = 2;
+
+/// Something the player does with a key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Dash,
+    AreaAttack,
+    Heal,
+    Ranged,
+    Descend,
+    Pause,
+    Confirm,
+    Shop1,
+    Shop2,
+    Shop3,
+    Shop4,
+}
+
+impl Action {
+    /// Every action, in the order they are listed for rebinding.
+    pub const ALL: [Action; 15] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Dash,
+        Action::AreaAttack,
+        Action::Heal,
+        Action::Ranged,
+        Action::Descend,
+        Action::Pause,
+        Action::Confirm,
+        Action::Shop1,
+        Action::Shop2,
+        Action::Shop3,
+        Action::Shop4,
+    ];
+
+    /// Shop slots in item order.
+    pub const SHOP_SLOTS: [Action; 4] =
+        [Action::Shop1, Action::Shop2, Action::Shop3, Action::Shop4];
+
+    /// Name shown on the controls screen.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Dash => "Dash",
+            Action::AreaAttack => "Area attack",
+            Action::Heal => "Heal",
+            Action::Ranged => "Ranged attack",
+            Action::Descend => "Descend stairs",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::Shop1 => "Buy item 1",
+            Action::Shop2 => "Buy item 2",
+            Action::Shop3 => "Buy item 3",
+            Action::Shop4 => "Buy item 4",
+        }
+    }
+
+    /// Name used in the settings file.
+    fn id(self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Dash => "dash",
+            Action::AreaAttack => "area_attack",
+            Action::Heal => "heal",
+            Action::Ranged => "ranged",
+            Action::Descend => "descend",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+            Action::Shop1 => "shop_1",
+            Action::Shop2 => "shop_2",
+            Action::Shop3 => "shop_3",
+            Action::Shop4 => "shop_4",
+        }
+    }
+
+    fn default_keys(self) -> [Option<KeyCode>; KEYS_PER_ACTION] {
+        use KeyCode::*;
+        let (primary, secondary) = match self {
+            Action::MoveUp => (W, Some(Up)),
+            Action::MoveDown => (S, Some(Down)),
+            Action::MoveLeft => (A, Some(Left)),
+            Action::MoveRight => (D, Some(Right)),
+            Action::Dash => (Space, None),
+            Action::AreaAttack => (Q, None),
+            Action::Heal => (E, None),
+            Action::Ranged => (R, None),
+            Action::Descend => (F, Some(Period)),
+            Action::Pause => (P, Some(Escape)),
+            Action::Confirm => (Enter, Some(KpEnter)),
+            Action::Shop1 => (Key1, Some(Kp1)),
+            Action::Shop2 => (Key2, Some(Kp2)),
+            Action::Shop3 => (Key3, Some(Kp3)),
+            Action::Shop4 => (Key4, Some(Kp4)),
+        };
+        [Some(primary), secondary]
+    }
+}
+
+/// Keys bound to every action. A key is bound to at most one action.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bindings {
+    keys: [[Option<KeyCode>; KEYS_PER_ACTION]; Action::ALL.len()],
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: Action::ALL.map(Action::default_keys),
+        }
+    }
+}
+
+impl Bindings {
+    /// Keys bound to `action`, primary first.
+    pub fn keys(&self, action: Action) -> [Option<KeyCode>; KEYS_PER_ACTION] {
+        self.keys[action as usize]
+    }
+
+    /// Bind `key` to `slot` of `action`, or clear the slot with `None`. The
+    /// key is taken from whatever action had it before.
+    pub fn set(&mut self, action: Action, slot: usize, key: Option<KeyCode>) {
+        if let Some((other, other_slot)) = key.and_then(|key| self.action_for(key)) {
+            self.keys[other as usize][other_slot] = None;
+        }
+        self.keys[action as usize][slot] = key;
+    }
+
+    /// Bind `key` to `slot` of `action`, handing the slot's old key to
+    /// whichever action had `key`. Refused, naming that action, when it
+    /// would be left with no keys at all.
+    pub fn swap(&mut self, action: Action, slot: usize, key: KeyCode) -> Result<(), Action> {
+        let old = self.keys(action)[slot];
+        let conflict = self.action_for(key).filter(|c| *c != (action, slot));
+        if let Some((other, _)) = conflict {
+            let last_key = self.keys(other).iter().flatten().count() == 1;
+            if other != action && old.is_none() && last_key {
+                return Err(other);
+            }
+        }
+        self.set(action, slot, Some(key));
+        if let Some((other, other_slot)) = conflict {
+            self.set(other, other_slot, old);
+        }
+        Ok(())
+    }
+
+    /// The action and slot `key` is bound to, if any.
+    pub fn action_for(&self, key: KeyCode) -> Option<(Action, usize)> {
+        Action::ALL.into_iter().find_map(|action| {
+            self.keys(action)
+                .iter()
+                .position(|k| *k == Some(key))
+                .map(|slot| (action, slot))
+        })
+    }
+
+    /// The first key of `action` for short hints.
+    pub fn primary_label(&self, action: Action) -> String {
+        self.keys(action)
+            .iter()
+            .flatten()
+            .next()
+            .map_or("unbound".to_string(), |k| key_name(*k))
+    }
+}
+
+impl Serialize for Bindings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let map: BTreeMap<&str, Vec<String>> = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = self
+                    .keys(action)
+                    .iter()
+                    .flatten()
+                    .map(|k| format!("{:?}", k))
+                    .collect();
+                (action.id(), keys)
+            })
+            .collect();
+        map.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bindings {
+    /// Actions missing from the file keep their defaults. Unknown actions and
+    /// keys are ignored, and a key listed twice stays with the first action.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = BTreeMap::<String, Vec<String>>::deserialize(deserializer)?;
+        let mut bindings = Bindings::default();
+        for action in Action::ALL {
+            if let Some(names) = map.get(action.id()) {
+                let mut keys = names.iter().filter_map(|name| parse_key(name));
+                bindings.keys[action as usize] = [keys.next(), keys.next()];
+            }
+        }
+
+        let mut seen = vec![];
+        for action in Action::ALL {
+            for key in &mut bindings.keys[action as usize] {
+                match *key {
+                    Some(k) if seen.contains(&k) => *key = None,
+                    Some(k) => seen.push(k),
+                    None => {}
+                }
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+/// Keys that can be bound.
+#[rustfmt::skip]
+const BINDABLE_KEYS: [KeyCode; 97] = {
+    use KeyCode::*;
+    [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        Space, Apostrophe, Comma, Minus, Period, Slash, Semicolon, Equal, LeftBracket,
+        Backslash, RightBracket, GraveAccent,
+        Escape, Enter, Tab, Backspace, Insert, Delete,
+        Right, Left, Down, Up, PageUp, PageDown, Home, End,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+        KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+        LeftShift, LeftControl, LeftAlt, RightShift, RightControl, RightAlt,
+    ]
+};
+
+/// Whether `key` can be bound to an action.
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+/// Key for a name written by the settings file.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .into_iter()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))
+}
+
+/// Short name of `key` for the screen.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    if let Some(digit) = name.strip_prefix("Key") {
+        digit.to_string()
+    } else if let Some(rest) = name.strip_prefix("Kp") {
+        format!("Num {}", rest)
+    } else {
+        name
+    }
+}
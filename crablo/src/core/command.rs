@@ -0,0 +1,32 @@
+//! Player commands.
+//!
+//! [`Game::step`](crate::core::Game::step) consumes these instead of reading
+//! the keyboard or mouse, so the simulation can be driven by input adapters,
+//! tests, bots or replays alike.
+
+use serde::{Deserialize, Serialize};
+
+/// A single player intention for one simulation step.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PlayerCommand {
+    /// Step one tile in the given map direction.
+    Move { dx: i32, dy: i32 },
+    /// Walk to a tile along the shortest path.
+    PathTo { x: usize, y: usize },
+    /// Melee attack the monster standing on a tile.
+    Attack { x: usize, y: usize },
+    /// Dash up to three tiles along the current path.
+    Dash,
+    /// Damage all adjacent monsters.
+    Area,
+    /// Restore a quarter of max HP.
+    Heal,
+    /// Hit the nearest monster within range.
+    Ranged,
+    /// Take the stairs the player is standing on down to the next floor.
+    Descend,
+    /// Buy the shop item at the given index.
+    ShopBuy(usize),
+    /// Close the shop and continue.
+    ShopLeave,
+}
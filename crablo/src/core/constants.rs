@@ -1,15 +1,28 @@
-// Map constants
-pub const MAP_SIZE: usize = 20;
+use serde::{Deserialize, Serialize};
+
+// Map constants. Floors start at the minimum size and grow two tiles every
+// few floors up to the maximum
+pub const MAP_MIN_SIZE: usize = 20;
+pub const MAP_MAX_SIZE: usize = 36;
+pub const MAP_GROWTH_FLOORS: usize = 3;
+// Every few floors a boss waits in an arena wider than the floors around it
+pub const BOSS_FLOOR_INTERVAL: i32 = 5;
+pub const BOSS_ARENA_EXTRA_WIDTH: usize = 8;
+// Every few floors one room holds a shop
+pub const SHOP_FLOOR_INTERVAL: i32 = 3;
 pub const TILE_WIDTH: f32 = 32.;
 pub const TILE_HEIGHT: f32 = 16.;
 
+// Camera position for a freshly started or loaded run, before it eases in
+pub const CAMERA_START: (f32, f32) = (400., 50.);
+
 // Player constants
 pub const PLAYER_START_HP: i32 = 100;
 pub const PLAYER_DAMAGE: i32 = 10;
 pub const PLAYER_MOVE_CD: f32 = 0.15;
 
-// Monster constants (base values, individual monsters may vary)
-pub const MONSTER_HP: i32 = 30;
+// Simulation
+pub const SIM_DT: f32 = 1. / 60.;
 
 // Scoring
 pub const GOLD_VALUE: i32 = 100;
@@ -20,7 +33,7 @@ pub const DMG_TEXT_DURATION: f32 = 1.0;
 pub const DMG_TEXT_SPEED: f32 = 20.;
 
 // Difficulty settings
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Difficulty {
     Easy,
     Normal,
@@ -28,6 +41,24 @@ pub enum Difficulty {
 }
 
 impl Difficulty {
+    /// Stable id used in the database.
+    pub fn id(&self) -> i32 {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Difficulty::Easy),
+            1 => Some(Difficulty::Normal),
+            2 => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
     pub fn monster_damage_mult(&self) -> f32 {
         match self {
             Difficulty::Easy => 0.5,
@@ -52,6 +83,15 @@ impl Difficulty {
         }
     }
 
+    /// Floor whose boss ends the run in victory once cleared.
+    pub fn final_floor(&self) -> i32 {
+        match self {
+            Difficulty::Easy => 15,
+            Difficulty::Normal => 20,
+            Difficulty::Hard => 25,
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Difficulty::Easy => "Easy",
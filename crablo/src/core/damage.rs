@@ -0,0 +1,109 @@
+//! Damage resolution.
+//!
+//! Every hit in a run - monster melee, traps, poison ticks and player
+//! abilities - is described as a [`Hit`] and resolved here, so armor and
+//! modifiers follow the same rules no matter where the damage comes from.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::traits::{DamageDealer, Damageable};
+
+/// Who or what dealt a hit.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum DamageSource {
+    Player,
+    /// A monster, by archetype name.
+    Monster(String),
+    Spikes,
+    Poison,
+}
+
+impl DamageSource {
+    pub fn name(&self) -> &str {
+        match self {
+            DamageSource::Player => "Player",
+            DamageSource::Monster(name) => name,
+            DamageSource::Spikes => "Spikes",
+            DamageSource::Poison => "Poison",
+        }
+    }
+}
+
+/// How a hit interacts with armor.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DamageType {
+    /// Blows and blades, reduced by armor.
+    Physical,
+    /// Damage over time that goes straight through armor.
+    Poison,
+}
+
+/// Adjustments applied on top of a hit's base amount.
+#[derive(Clone, Copy)]
+pub struct DamageModifiers {
+    /// Scales the base amount before armor (rounded down).
+    pub multiplier: f32,
+    /// Most armor that can count against this hit.
+    pub armor_cap: Option<i32>,
+    /// Damage that always gets through.
+    pub minimum: i32,
+}
+
+impl Default for DamageModifiers {
+    fn default() -> Self {
+        DamageModifiers {
+            multiplier: 1.,
+            armor_cap: None,
+            minimum: 1,
+        }
+    }
+}
+
+/// A single hit waiting to be resolved.
+#[derive(Clone)]
+pub struct Hit {
+    pub source: DamageSource,
+    pub kind: DamageType,
+    pub amount: i32,
+    pub modifiers: DamageModifiers,
+}
+
+impl Hit {
+    pub fn new(source: DamageSource, kind: DamageType, amount: i32) -> Self {
+        Hit {
+            source,
+            kind,
+            amount,
+            modifiers: DamageModifiers::default(),
+        }
+    }
+
+    /// A hit for the dealer's base damage.
+    pub fn from_dealer(source: DamageSource, kind: DamageType, dealer: &impl DamageDealer) -> Self {
+        Hit::new(source, kind, dealer.damage())
+    }
+
+    pub fn with_modifiers(mut self, modifiers: DamageModifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Final damage of this hit against a target wearing `armor`.
+    pub fn amount_against(&self, armor: i32) -> i32 {
+        let scaled = (self.amount as f32 * self.modifiers.multiplier) as i32;
+        let armor = match self.kind {
+            DamageType::Physical => match self.modifiers.armor_cap {
+                Some(cap) => armor.min(cap),
+                None => armor,
+            },
+            DamageType::Poison => 0,
+        };
+        (scaled - armor.max(0)).max(self.modifiers.minimum)
+    }
+}
+
+/// Resolve `hit` against `target` and return the damage actually taken.
+pub fn resolve<T: Damageable + ?Sized>(target: &mut T, hit: &Hit) -> i32 {
+    let amount = hit.amount_against(target.armor());
+    target.take_damage(amount)
+}
@@ -1,155 +1,389 @@
-use rusqlite::{Connection, Result};
-use std::path::PathBuf;
+//! SQLite storage backend, with `user_version` schema migrations.
 
-pub struct ScoreEntry {
-    pub rank: i32,
-    pub name: String,
-    pub score: i32,
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::path::{Path, PathBuf};
+
+use crate::core::constants::Difficulty;
+use crate::core::storage::{self, RunStats, SaveData, SaveSlot, ScoreEntry, Storage, StorageError};
+
+/// Schema upgrades, in order. Step `i` takes a database from version `i` to
+/// `i + 1`; append a new step whenever the schema changes.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    initial_schema,
+    seeds_replays_and_snapshots,
+    gold_wallet,
+    run_stats,
+    save_slots,
+    session_marker,
+];
+
+/// Schema version written by this build, stored in `PRAGMA user_version`.
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
+
+const DB_FILE_NAME: &str = "crablo_scores.db";
+
+/// Bring the schema up to [`SCHEMA_VERSION`] in a single transaction, so a
+/// failed step leaves the file exactly as it was.
+fn migrate(conn: &mut Connection) -> storage::Result<()> {
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(StorageError::NewerVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    let tx = conn.transaction()?;
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+        step(&tx)?;
+        tx.pragma_update(None, "user_version", i as i32 + 1)?;
+    }
+    tx.commit()?;
+    Ok(())
 }
 
-pub struct SaveData {
-    pub floor: i32,
-    pub hp: i32,
-    pub max_hp: i32,
-    pub score: i32,
-    pub difficulty: i32,
+/// Version 1: the original scores and saves tables.
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS scores (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            score INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS saves (
+            id INTEGER PRIMARY KEY,
+            floor INTEGER NOT NULL,
+            hp INTEGER NOT NULL,
+            max_hp INTEGER NOT NULL,
+            score INTEGER NOT NULL,
+            difficulty INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );",
+    )
 }
 
-pub struct Database {
-    conn: Connection,
+/// Version 2: run seeds and replays on scores, game snapshots on saves.
+fn seeds_replays_and_snapshots(conn: &Connection) -> Result<()> {
+    add_column(conn, "scores", "seed", "INTEGER")?;
+    add_column(conn, "scores", "replay", "TEXT")?;
+    add_column(conn, "saves", "snapshot", "TEXT")
 }
 
-impl Database {
-    /// Get the database path - always next to the executable
-    fn db_path() -> PathBuf {
-        if let Ok(exe_path) = std::env::current_exe() {
-            if let Some(exe_dir) = exe_path.parent() {
-                return exe_dir.join("crablo_scores.db");
-            }
+/// Version 3: gold wallet shown in save previews.
+fn gold_wallet(conn: &Connection) -> Result<()> {
+    add_column(conn, "saves", "gold", "INTEGER NOT NULL DEFAULT 0")
+}
+
+/// Version 4: per-run stats for the Hall of Fame.
+fn run_stats(conn: &Connection) -> Result<()> {
+    add_column(conn, "scores", "won", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(conn, "scores", "difficulty", "INTEGER")?;
+    for column in [
+        "floor",
+        "level",
+        "kills",
+        "gold",
+        "damage_dealt",
+        "duration",
+    ] {
+        add_column(conn, "scores", column, "INTEGER NOT NULL DEFAULT 0")?;
+    }
+    add_column(conn, "scores", "cause", "TEXT")
+}
+
+/// Version 5: several named save slots. The old single save becomes slot 1.
+fn save_slots(conn: &Connection) -> Result<()> {
+    add_column(conn, "saves", "slot", "INTEGER")?;
+    add_column(conn, "saves", "name", "TEXT NOT NULL DEFAULT ''")?;
+    add_column(conn, "saves", "level", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(conn, "saves", "playtime", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute_batch(
+        "DELETE FROM saves WHERE id NOT IN (SELECT MAX(id) FROM saves);
+         UPDATE saves SET slot = 1 WHERE slot IS NULL;
+         CREATE UNIQUE INDEX IF NOT EXISTS saves_slot ON saves (slot);",
+    )
+}
+
+/// Version 6: a key/value table holding the running-session marker.
+fn session_marker(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )
+}
+
+/// Add a column unless it is already there. Databases written before
+/// versioning existed may already carry some of the later columns.
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(());
         }
-        // Fallback to current directory
-        PathBuf::from("crablo_scores.db")
     }
+    conn.execute(
+        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+        [],
+    )?;
+    Ok(())
+}
 
-    pub fn new() -> Result<Self> {
-        let db_path = Self::db_path();
-        let conn = Connection::open(&db_path)?;
+pub struct Database {
+    conn: Connection,
+}
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS scores (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL,
-                score INTEGER NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        )?;
+impl Database {
+    /// Default database location in the platform data directory.
+    pub fn default_path() -> PathBuf {
+        storage::data_dir().join(DB_FILE_NAME)
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS saves (
-                id INTEGER PRIMARY KEY,
-                floor INTEGER NOT NULL,
-                hp INTEGER NOT NULL,
-                max_hp INTEGER NOT NULL,
-                score INTEGER NOT NULL,
-                difficulty INTEGER NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        )?;
+    /// Copy a database left next to the executable by older builds to
+    /// `path`, so existing scores and saves carry over. Nothing happens once
+    /// `path` exists; the copy goes through a temporary file so a failed one
+    /// is tried again next time rather than leaving an empty database behind.
+    pub fn adopt_legacy(path: &Path) -> storage::Result<()> {
+        let legacy = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(DB_FILE_NAME)));
+        let Some(legacy) = legacy.filter(|legacy| legacy.exists() && !path.exists()) else {
+            return Ok(());
+        };
+        let copy = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let partial = path.with_extension("db.partial");
+            std::fs::copy(&legacy, &partial)?;
+            std::fs::rename(&partial, path)
+        };
+        copy().map_err(|e| {
+            let message = format!("couldn't copy {}: {}", legacy.display(), e);
+            std::io::Error::new(e.kind(), message).into()
+        })
+    }
 
+    /// Open the database at `path`, creating or upgrading its schema.
+    pub fn open(path: &Path) -> storage::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut conn = Connection::open(path)?;
+        migrate(&mut conn)?;
         Ok(Database { conn })
     }
 
-    pub fn save_score(&self, name: &str, score: i32) -> Result<()> {
+    #[allow(dead_code)]
+    pub fn is_high_score(&self, score: i32) -> storage::Result<bool> {
+        let top_scores = self.get_top_scores(10, None)?;
+        if top_scores.len() < 10 {
+            return Ok(true);
+        }
+        Ok(score > top_scores.last().map(|s| s.score).unwrap_or(0))
+    }
+}
+
+impl Storage for Database {
+    fn save_score(
+        &mut self,
+        name: &str,
+        score: i32,
+        seed: u64,
+        replay: &str,
+        stats: &RunStats,
+    ) -> storage::Result<()> {
+        // SQLite integers are signed; the seed round-trips through the cast
         self.conn.execute(
-            "INSERT INTO scores (name, score) VALUES (?1, ?2)",
-            [name, &score.to_string()],
+            "INSERT INTO scores (name, score, seed, replay, won, difficulty, floor, level,
+                                 kills, gold, damage_dealt, duration, cause)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                name,
+                score,
+                seed as i64,
+                replay,
+                stats.won,
+                stats.difficulty.map(|d| d.id()),
+                stats.floor,
+                stats.level,
+                stats.kills,
+                stats.gold,
+                stats.damage_dealt,
+                stats.duration,
+                stats.cause
+            ],
         )?;
         Ok(())
     }
 
-    pub fn get_top_scores(&self, limit: i32) -> Result<Vec<ScoreEntry>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT name, score FROM scores ORDER BY score DESC LIMIT ?1")?;
+    fn get_top_scores(
+        &self,
+        limit: i32,
+        difficulty: Option<Difficulty>,
+    ) -> storage::Result<Vec<ScoreEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, score, seed, replay IS NOT NULL, won, difficulty, floor, level,
+                    kills, gold, damage_dealt, duration, cause, created_at
+             FROM scores
+             WHERE ?2 IS NULL OR difficulty = ?2
+             ORDER BY score DESC LIMIT ?1",
+        )?;
 
-        let scores = stmt.query_map([limit], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+        let scores = stmt.query_map(params![limit, difficulty.map(|d| d.id())], |row| {
+            Ok(ScoreEntry {
+                id: row.get(0)?,
+                rank: 0,
+                name: row.get(1)?,
+                score: row.get(2)?,
+                seed: row.get::<_, Option<i64>>(3)?.map(|s| s as u64),
+                has_replay: row.get(4)?,
+                stats: RunStats {
+                    won: row.get(5)?,
+                    difficulty: row.get::<_, Option<i32>>(6)?.and_then(Difficulty::from_id),
+                    floor: row.get(7)?,
+                    level: row.get(8)?,
+                    kills: row.get(9)?,
+                    gold: row.get(10)?,
+                    damage_dealt: row.get(11)?,
+                    duration: row.get(12)?,
+                    cause: row.get(13)?,
+                },
+                created_at: row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+            })
         })?;
 
         let mut result = Vec::new();
-        for (i, score) in scores.enumerate() {
-            let (name, score_val) = score?;
-            result.push(ScoreEntry {
-                rank: (i + 1) as i32,
-                name,
-                score: score_val,
-            });
+        for (i, entry) in scores.enumerate() {
+            let mut entry = entry?;
+            entry.rank = (i + 1) as i32;
+            result.push(entry);
         }
 
         Ok(result)
     }
 
-    #[allow(dead_code)]
-    pub fn is_high_score(&self, score: i32) -> Result<bool> {
-        let top_scores = self.get_top_scores(10)?;
-        if top_scores.len() < 10 {
-            return Ok(true);
-        }
-        Ok(score > top_scores.last().map(|s| s.score).unwrap_or(0))
+    fn load_replay(&self, score_id: i64) -> storage::Result<Option<String>> {
+        let replay = self
+            .conn
+            .query_row(
+                "SELECT replay FROM scores WHERE id = ?1",
+                [score_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(replay.flatten())
     }
 
-    pub fn reset_scores(&self) -> Result<()> {
+    fn reset_scores(&mut self) -> storage::Result<()> {
         self.conn.execute("DELETE FROM scores", [])?;
         Ok(())
     }
 
-    pub fn save_game(
-        &self,
-        floor: i32,
-        hp: i32,
-        max_hp: i32,
-        score: i32,
-        difficulty: i32,
-    ) -> Result<()> {
-        // Delete old save first (only one save slot)
-        self.conn.execute("DELETE FROM saves", [])?;
-        self.conn.execute(
-            "INSERT INTO saves (floor, hp, max_hp, score, difficulty) VALUES (?1, ?2, ?3, ?4, ?5)",
-            [floor, hp, max_hp, score, difficulty],
+    fn save_game(&mut self, slot: i32, save: &SaveData) -> storage::Result<()> {
+        // Replace the slot in one transaction so it is never left empty
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM saves WHERE slot = ?1", [slot])?;
+        tx.execute(
+            "INSERT INTO saves (slot, name, floor, level, hp, max_hp, score, gold, difficulty,
+                                playtime, snapshot)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                slot,
+                save.name,
+                save.floor,
+                save.level,
+                save.hp,
+                save.max_hp,
+                save.score,
+                save.gold,
+                save.difficulty,
+                save.playtime,
+                save.snapshot
+            ],
         )?;
+        tx.commit()?;
         Ok(())
     }
 
-    pub fn load_game(&self) -> Result<Option<SaveData>> {
+    fn load_game(&self, slot: i32) -> storage::Result<Option<SaveData>> {
+        // Saves written before snapshots existed cannot be resumed
+        let save = self
+            .conn
+            .query_row(
+                "SELECT name, floor, level, hp, max_hp, score, gold, difficulty, playtime, snapshot
+                 FROM saves WHERE slot = ?1 AND snapshot IS NOT NULL",
+                [slot],
+                |row| {
+                    Ok(SaveData {
+                        name: row.get(0)?,
+                        floor: row.get(1)?,
+                        level: row.get(2)?,
+                        hp: row.get(3)?,
+                        max_hp: row.get(4)?,
+                        score: row.get(5)?,
+                        gold: row.get(6)?,
+                        difficulty: row.get(7)?,
+                        playtime: row.get(8)?,
+                        snapshot: row.get(9)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(save)
+    }
+
+    fn delete_save(&mut self, slot: i32) -> storage::Result<()> {
+        self.conn
+            .execute("DELETE FROM saves WHERE slot = ?1", [slot])?;
+        Ok(())
+    }
+
+    fn list_saves(&self) -> storage::Result<Vec<SaveSlot>> {
         let mut stmt = self.conn.prepare(
-            "SELECT floor, hp, max_hp, score, difficulty FROM saves ORDER BY id DESC LIMIT 1",
+            "SELECT slot, name, floor, level, gold, difficulty, playtime, created_at
+             FROM saves WHERE snapshot IS NOT NULL ORDER BY slot",
         )?;
-
-        let mut rows = stmt.query([])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(SaveData {
-                floor: row.get(0)?,
-                hp: row.get(1)?,
-                max_hp: row.get(2)?,
-                score: row.get(3)?,
-                difficulty: row.get(4)?,
-            }))
-        } else {
-            Ok(None)
-        }
+        let slots = stmt.query_map([], |row| {
+            Ok(SaveSlot {
+                slot: row.get(0)?,
+                name: row.get(1)?,
+                floor: row.get(2)?,
+                level: row.get(3)?,
+                gold: row.get(4)?,
+                difficulty: Difficulty::from_id(row.get(5)?),
+                playtime: row.get(6)?,
+                saved_at: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+            })
+        })?;
+        Ok(slots.collect::<Result<_>>()?)
     }
 
-    pub fn delete_save(&self) -> Result<()> {
-        self.conn.execute("DELETE FROM saves", [])?;
-        Ok(())
+    fn begin_session(&mut self) -> storage::Result<bool> {
+        let tx = self.conn.transaction()?;
+        let open = tx
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'session_open'",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('session_open', '1')",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(open.as_deref() == Some("1"))
     }
 
-    pub fn has_save(&self) -> Result<bool> {
-        let count: i32 = self
-            .conn
-            .query_row("SELECT COUNT(*) FROM saves", [], |row| row.get(0))?;
-        Ok(count > 0)
+    fn end_session(&mut self) -> storage::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('session_open', '0')",
+            [],
+        )?;
+        Ok(())
     }
 }
@@ -1,15 +1,21 @@
-use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::core::command::PlayerCommand;
 use crate::core::constants::*;
-use crate::core::database::SaveData;
+use crate::core::damage::{self, DamageModifiers, DamageSource, DamageType, Hit};
 use crate::core::player::Player;
+use crate::core::replay::Replay;
+use crate::core::rng::Rng;
 use crate::core::shop::{create_shop_items, try_purchase};
-use crate::core::traits::{DamageDealer, Damageable};
-use crate::systems::game_renderer::{render_game, GameRenderData};
+use crate::core::storage::{RunStats, SaveData};
+use crate::core::traits::Damageable;
+use crate::systems::game_renderer::{render_game, GameRenderData, RenderStyle};
 use crate::systems::pathfinding::{bfs, dist};
-use crate::systems::rendering::{to_screen, to_tile};
+use crate::systems::rendering::to_screen;
+use crate::world::bestiary::bestiary;
 use crate::world::entities::{DmgText, EquipmentType, Monster, ShopItem};
-use crate::world::map::{create_map, get_player_spawn, Tile};
+use crate::world::generator::generate_floor;
+use crate::world::map::{Map, Room, RoomRole, Tile};
 
 #[derive(Default)]
 pub struct GameEvents {
@@ -18,22 +24,39 @@ pub struct GameEvents {
     pub gold_collected: bool,
     pub monster_killed: bool,
     pub floor_completed: bool,
+    /// The player left the shop.
+    pub shop_closed: bool,
+    /// The final floor was cleared. Always comes with `game_over`.
+    pub victory: bool,
     pub game_over: bool,
 }
 
+/// Complete state of a run. Everything except transient visuals is
+/// serialized into save snapshots so a loaded game resumes exactly.
+#[derive(Serialize, Deserialize)]
 pub struct Game {
-    pub map: [[Tile; MAP_SIZE]; MAP_SIZE],
+    pub map: Map,
+    /// Rooms of the current floor, the player's first.
+    #[serde(default)]
+    pub rooms: Vec<Room>,
+    #[serde(skip)]
     pub cam: (f32, f32),
     pub player: Player,
     pub monsters: Vec<Monster>,
+    #[serde(skip)]
     pub texts: Vec<DmgText>,
     pub gold: Vec<(usize, usize)>,
     pub potions: Vec<(usize, usize)>,
     pub score: i32,
     pub difficulty: Difficulty,
+    /// Run seed; every floor layout is derived from it.
+    pub seed: u64,
+    /// Generator for in-run rolls such as loot drops.
+    pub rng: Rng,
     pub floor: i32,
     pub shake: f32,
-    pub explored: [[bool; MAP_SIZE]; MAP_SIZE],
+    /// Tiles the player has seen, the same size as `map`.
+    pub explored: Map<bool>,
     // Traps
     pub spikes: Vec<(usize, usize)>,
     pub poison: Vec<(usize, usize)>,
@@ -43,9 +66,23 @@ pub struct Game {
     pub total_kills: i32,
     pub total_gold: i32,
     pub total_damage_dealt: i32,
+    /// What landed the killing blow on the player.
+    pub death_cause: Option<DamageSource>,
+    /// Whether the final floor of the difficulty was cleared.
+    #[serde(default)]
+    pub won: bool,
     // Shop
     pub in_shop: bool,
+    /// Stock of this floor's shop, empty until the player first walks in.
     pub shop_items: Vec<ShopItem>,
+    /// Every command fed into the run so far.
+    pub replay: Replay,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
@@ -53,205 +90,174 @@ impl Game {
         Self::with_difficulty(Difficulty::Normal)
     }
 
-    pub fn from_save(save: &SaveData) -> Self {
-        let difficulty = match save.difficulty {
-            0 => Difficulty::Easy,
-            2 => Difficulty::Hard,
-            _ => Difficulty::Normal,
-        };
-        let mut game = Self::with_difficulty(difficulty);
-        game.floor = save.floor;
-        game.player.hp = save.hp;
-        game.player.max_hp = save.max_hp;
-        game.score = save.score;
-        // Regenerate the floor with proper scaling
-        if save.floor > 1 {
-            game.floor = save.floor - 1;
-            game.next_floor();
-            game.player.hp = save.hp; // Restore HP after next_floor
-        }
-        game
+    /// Restore a run from its saved snapshot.
+    pub fn from_save(save: &SaveData) -> serde_json::Result<Self> {
+        let mut game: Game = serde_json::from_str(&save.snapshot)?;
+        game.cam = CAMERA_START;
+        Ok(game)
+    }
+
+    /// Snapshot the whole run into a save record. The slot name is left
+    /// for the caller to fill in.
+    pub fn to_save(&self) -> serde_json::Result<SaveData> {
+        Ok(SaveData {
+            name: String::new(),
+            floor: self.floor,
+            level: self.player.level,
+            hp: self.player.hp,
+            max_hp: self.player.max_hp,
+            score: self.score,
+            gold: self.player.gold,
+            difficulty: self.get_difficulty_id(),
+            playtime: self.playtime() as i32,
+            snapshot: serde_json::to_string(self)?,
+        })
+    }
+
+    /// Seconds of play so far, counted in fixed simulation steps.
+    pub fn playtime(&self) -> f32 {
+        self.replay.frames as f32 * SIM_DT
     }
 
     pub fn get_difficulty_id(&self) -> i32 {
-        match self.difficulty {
-            Difficulty::Easy => 0,
-            Difficulty::Normal => 1,
-            Difficulty::Hard => 2,
+        self.difficulty.id()
+    }
+
+    /// Summary of the run for the Hall of Fame.
+    pub fn run_stats(&self) -> RunStats {
+        RunStats {
+            difficulty: Some(self.difficulty),
+            floor: self.floor,
+            level: self.player.level,
+            kills: self.total_kills,
+            gold: self.total_gold,
+            damage_dealt: self.total_damage_dealt,
+            duration: self.playtime() as i32,
+            cause: match &self.death_cause {
+                Some(cause) if !self.won => Some(cause.name().to_string()),
+                _ => None,
+            },
+            won: self.won,
         }
     }
 
     pub fn with_difficulty(difficulty: Difficulty) -> Self {
-        let (map, gold_positions, monster_positions) = create_map();
-        let (px, py) = get_player_spawn(&map);
-
-        let hp_mult = difficulty.monster_hp_mult();
-        let monsters = monster_positions
-            .into_iter()
-            .map(|(x, y, mtype)| {
-                let mut m = match mtype {
-                    1 => Monster::new_fast(x, y),
-                    2 => Monster::new_tank(x, y),
-                    _ => Monster::new(x, y),
-                };
-                m.hp = (m.hp as f32 * hp_mult) as i32;
-                m.damage = (m.damage as f32 * difficulty.monster_damage_mult()) as i32;
-                m
-            })
-            .collect();
+        Self::with_seed(difficulty, Rng::random_seed())
+    }
 
-        // Generate potion positions in some rooms
-        let potions = gold_positions
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i % 2 == 0)
-            .map(|(_, &pos)| (pos.0.saturating_add(1), pos.1))
-            .filter(|&(x, y)| x < MAP_SIZE && y < MAP_SIZE && map[y][x] == Tile::Floor)
-            .collect();
+    /// Start a run whose floors are fully determined by `seed` and `difficulty`.
+    pub fn with_seed(difficulty: Difficulty, seed: u64) -> Self {
+        let mut rng = Rng::for_floor(seed, 1);
+        let plan = generate_floor(&mut rng, 1);
+        let (px, py) = plan.spawn;
+
+        let monsters = spawn_monsters(
+            &plan.monsters,
+            1,
+            difficulty.monster_hp_mult(),
+            difficulty.monster_damage_mult(),
+            &mut rng,
+        );
 
         let _player_hp = (PLAYER_START_HP as f32 * difficulty.player_hp_mult()) as i32;
 
         // Initialize explored map - start with player's area visible
-        let mut explored = [[false; MAP_SIZE]; MAP_SIZE];
-        for dy in -3i32..=3 {
-            for dx in -3i32..=3 {
-                let ex = px as i32 + dx;
-                let ey = py as i32 + dy;
-                if ex >= 0 && ey >= 0 && (ex as usize) < MAP_SIZE && (ey as usize) < MAP_SIZE {
-                    explored[ey as usize][ex as usize] = true;
-                }
-            }
-        }
-
-        // Generate traps
-        let spikes: Vec<(usize, usize)> = gold_positions
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i % 4 == 0)
-            .map(|(_, &pos)| (pos.0.saturating_sub(1), pos.1.saturating_add(1)))
-            .filter(|&(x, y)| x < MAP_SIZE && y < MAP_SIZE && map[y][x] == Tile::Floor)
-            .collect();
-
-        let poison: Vec<(usize, usize)> = gold_positions
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i % 5 == 0)
-            .map(|(_, &pos)| (pos.0.saturating_add(2), pos.1))
-            .filter(|&(x, y)| x < MAP_SIZE && y < MAP_SIZE && map[y][x] == Tile::Floor)
-            .collect();
+        let mut explored = plan.map.same_size(false);
+        reveal(&mut explored, (px, py), 3);
 
         Game {
-            map,
-            cam: (screen_width() / 2., 50.),
+            map: plan.map,
+            rooms: plan.rooms,
+            cam: CAMERA_START,
             player: Player::new(px, py, difficulty),
             monsters,
             texts: vec![],
             score: 0,
-            gold: gold_positions,
-            potions,
+            gold: plan.gold,
+            potions: plan.potions,
             difficulty,
+            seed,
+            rng: Rng::new(seed),
             floor: 1,
             shake: 0.,
             explored,
-            spikes,
-            poison,
+            spikes: plan.spikes,
+            poison: plan.poison,
             equipment_drops: vec![],
             total_kills: 0,
             total_gold: 0,
             total_damage_dealt: 0,
+            death_cause: None,
+            won: false,
             in_shop: false,
             shop_items: vec![],
+            replay: Replay::new(seed, difficulty),
         }
     }
 
     pub fn next_floor(&mut self) {
         self.floor += 1;
-        let (map, gold_positions, monster_positions) = create_map();
-        let (px, py) = get_player_spawn(&map);
+        let mut rng = Rng::for_floor(self.seed, self.floor);
+        let plan = generate_floor(&mut rng, self.floor);
+        let (px, py) = plan.spawn;
 
         let hp_mult = self.difficulty.monster_hp_mult() * (1.0 + self.floor as f32 * 0.1);
         let dmg_mult = self.difficulty.monster_damage_mult() * (1.0 + self.floor as f32 * 0.1);
-        let is_boss_floor = self.floor % 5 == 0;
-
-        self.monsters = monster_positions
-            .into_iter()
-            .enumerate()
-            .map(|(i, (x, y, mtype))| {
-                // First monster on boss floors is a boss
-                let mut m = if is_boss_floor && i == 0 {
-                    Monster::new_boss(x, y)
-                } else {
-                    match mtype {
-                        1 => Monster::new_fast(x, y),
-                        2 => Monster::new_tank(x, y),
-                        _ => Monster::new(x, y),
-                    }
-                };
-                m.hp = (m.hp as f32 * hp_mult) as i32;
-                m.damage = (m.damage as f32 * dmg_mult) as i32;
-                m
-            })
-            .collect();
-
-        self.potions = gold_positions
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i % 2 == 0)
-            .map(|(_, &pos)| (pos.0.saturating_add(1), pos.1))
-            .filter(|&(x, y)| x < MAP_SIZE && y < MAP_SIZE && map[y][x] == Tile::Floor)
-            .collect();
-
-        self.map = map;
-        self.gold = gold_positions;
+        self.monsters = spawn_monsters(&plan.monsters, self.floor, hp_mult, dmg_mult, &mut rng);
+
+        self.map = plan.map;
+        self.rooms = plan.rooms;
+        self.gold = plan.gold;
+        self.potions = plan.potions;
+        self.spikes = plan.spikes;
+        self.poison = plan.poison;
         self.player.x = px;
         self.player.y = py;
         self.player.path.clear();
-        self.explored = [[false; MAP_SIZE]; MAP_SIZE];
+        self.explored = self.map.same_size(false);
 
         // Reveal starting area
-        for dy in -3i32..=3 {
-            for dx in -3i32..=3 {
-                let ex = px as i32 + dx;
-                let ey = py as i32 + dy;
-                if ex >= 0 && ey >= 0 && (ex as usize) < MAP_SIZE && (ey as usize) < MAP_SIZE {
-                    self.explored[ey as usize][ex as usize] = true;
-                }
-            }
-        }
+        reveal(&mut self.explored, (px, py), 3);
 
         // Bonus score for completing floor
         self.score += 500 * (self.floor - 1);
 
-        // Generate new traps
-        self.spikes = self
-            .gold
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i % 4 == 0)
-            .map(|(_, &pos)| (pos.0.saturating_sub(1), pos.1.saturating_add(1)))
-            .filter(|&(x, y)| x < MAP_SIZE && y < MAP_SIZE && self.map[y][x] == Tile::Floor)
-            .collect();
+        self.equipment_drops.clear();
+        self.shop_items.clear();
+    }
 
-        self.poison = self
-            .gold
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i % 5 == 0)
-            .map(|(_, &pos)| (pos.0.saturating_add(2), pos.1))
-            .filter(|&(x, y)| x < MAP_SIZE && y < MAP_SIZE && self.map[y][x] == Tile::Floor)
-            .collect();
+    /// Whether the player stands on the stairs down.
+    pub fn on_stairs(&self) -> bool {
+        self.map.get(self.player.x, self.player.y) == Some(Tile::StairsDown)
+    }
 
-        self.equipment_drops.clear();
+    /// Whether the stairs stay shut: on boss floors they open once the boss
+    /// is dead.
+    pub fn stairs_sealed(&self) -> bool {
+        self.monsters.iter().any(|m| m.boss)
+    }
 
-        // Show shop every 3 floors
-        if self.floor % 3 == 0 {
-            self.in_shop = true;
-            self.shop_items = create_shop_items();
-        }
+    /// Whether a descend command would take the stairs right now.
+    pub fn can_descend(&self) -> bool {
+        self.on_stairs() && !self.stairs_sealed()
+    }
+
+    /// The shop room of this floor, if it has one.
+    pub fn shop_room(&self) -> Option<&Room> {
+        self.rooms.iter().find(|room| room.role == RoomRole::Shop)
     }
 
-    pub fn update(&mut self, dt: f32) -> GameEvents {
+    /// Advance the simulation by `dt` seconds, applying the given commands.
+    ///
+    /// This never touches the window or input devices; see
+    /// [`poll_commands`](crate::systems::input::poll_commands) for the
+    /// macroquad input adapter.
+    ///
+    /// Runs are only reproducible from their [`Replay`] when stepped with a
+    /// fixed `dt` of [`SIM_DT`].
+    pub fn step(&mut self, commands: &[PlayerCommand], dt: f32) -> GameEvents {
         let mut events = GameEvents::default();
+        self.replay.record(commands);
 
         // Update screen shake
         if self.shake > 0. {
@@ -280,26 +286,36 @@ impl Game {
             self.player.poisoned -= dt;
             // Poison does 2 damage per second
             if (self.player.poisoned * 10.) as i32 % 10 == 0 {
-                Damageable::take_damage(&mut self.player, 1);
+                self.hit_player(Hit::new(DamageSource::Poison, DamageType::Poison, 1));
                 self.shake = 0.2;
             }
         }
 
         // Shop handling - skip normal gameplay while in shop
         if self.in_shop {
-            // Shop input handled in draw, just return
+            for command in commands {
+                self.apply_shop_command(*command);
+            }
+            events.shop_closed = !self.in_shop;
             return events;
         }
 
-        if self.player.is_dead() {
+        if self.player.is_dead() || self.won {
             events.game_over = true;
             return events;
         }
 
-        // All monsters dead - go to next floor
-        if self.monsters.is_empty() {
-            self.next_floor();
-            events.floor_completed = true;
+        // Taking the stairs ends the floor; on the final floor they lead out
+        // of the dungeon and win the run
+        if commands.contains(&PlayerCommand::Descend) && self.can_descend() {
+            if self.floor >= self.difficulty.final_floor() {
+                self.won = true;
+                events.victory = true;
+                events.game_over = true;
+            } else {
+                self.next_floor();
+                events.floor_completed = true;
+            }
             return events;
         }
 
@@ -310,146 +326,8 @@ impl Game {
             t.life > 0.
         });
 
-        // Dash ability (Space key)
-        if is_key_pressed(KeyCode::Space)
-            && self.player.dash_cd <= 0.
-            && !self.player.path.is_empty()
-        {
-            self.player.dash_cd = 2.0; // 2 second cooldown
-            for _ in 0..3 {
-                if self.player.path.is_empty() {
-                    break;
-                }
-                let (nx, ny) = self.player.path[0];
-                if self.monsters.iter().any(|m| m.x == nx && m.y == ny) {
-                    break;
-                }
-                self.player.path.remove(0);
-                self.player.x = nx;
-                self.player.y = ny;
-            }
-        }
-
-        // Area attack ability (Q key) - damages all adjacent monsters
-        if is_key_pressed(KeyCode::Q) && self.player.area_cd <= 0. {
-            self.player.area_cd = 3.0; // 3 second cooldown
-            let mut killed_any = false;
-            let px = self.player.x as i32;
-            let py = self.player.y as i32;
-
-            // Find all adjacent monsters
-            let adjacent: Vec<usize> = self
-                .monsters
-                .iter()
-                .enumerate()
-                .filter(|(_, m)| {
-                    let dx = (m.x as i32 - px).abs();
-                    let dy = (m.y as i32 - py).abs();
-                    dx <= 1 && dy <= 1 && (dx + dy) > 0
-                })
-                .map(|(i, _)| i)
-                .collect();
-
-            // Damage them in reverse order to avoid index issues
-            for i in adjacent.into_iter().rev() {
-                if self.damage_monster(i, self.player.damage() / 2) {
-                    killed_any = true;
-                }
-            }
-            if killed_any {
-                events.monster_killed = true;
-            }
-            events.monster_hit = true;
-        }
-
-        // Heal ability (E key) - restore HP
-        if is_key_pressed(KeyCode::E)
-            && self.player.heal_cd <= 0.
-            && Damageable::hp(&self.player) < Damageable::max_hp(&self.player)
-        {
-            self.player.heal_cd = 5.0; // 5 second cooldown
-            let heal_amount = Damageable::max_hp(&self.player) / 4;
-            Damageable::heal(&mut self.player, heal_amount);
-
-            let (sx, sy) = to_screen(self.player.x, self.player.y, self.cam);
-            self.texts.push(DmgText {
-                x: sx,
-                y: sy - 40.,
-                dmg: -heal_amount,
-                life: DMG_TEXT_DURATION,
-            });
-        }
-
-        // Ranged attack ability (R key) - attack nearest monster in range
-        if is_key_pressed(KeyCode::R) && self.player.ranged_cd <= 0. {
-            let px = self.player.x as i32;
-            let py = self.player.y as i32;
-
-            // Find nearest monster within range 5
-            if let Some((idx, _)) = self
-                .monsters
-                .iter()
-                .enumerate()
-                .filter(|(_, m)| {
-                    let dx = (m.x as i32 - px).abs();
-                    let dy = (m.y as i32 - py).abs();
-                    dx <= 5 && dy <= 5
-                })
-                .min_by_key(|(_, m)| {
-                    let dx = (m.x as i32 - px).abs();
-                    let dy = (m.y as i32 - py).abs();
-                    dx + dy
-                })
-            {
-                self.player.ranged_cd = 1.5; // 1.5 second cooldown
-                if self.damage_monster(idx, self.player.damage()) {
-                    events.monster_killed = true;
-                }
-                events.monster_hit = true;
-            }
-        }
-
-        // WASD keyboard movement
-        let mut move_dir: Option<(i32, i32)> = None;
-        if is_key_pressed(KeyCode::W) || is_key_pressed(KeyCode::Up) {
-            move_dir = Some((-1, -1)); // Up-left in isometric
-        } else if is_key_pressed(KeyCode::S) || is_key_pressed(KeyCode::Down) {
-            move_dir = Some((1, 1)); // Down-right in isometric
-        } else if is_key_pressed(KeyCode::A) || is_key_pressed(KeyCode::Left) {
-            move_dir = Some((-1, 1)); // Down-left in isometric
-        } else if is_key_pressed(KeyCode::D) || is_key_pressed(KeyCode::Right) {
-            move_dir = Some((1, -1)); // Up-right in isometric
-        }
-
-        if let Some((dx, dy)) = move_dir {
-            let nx = (self.player.x as i32 + dx) as usize;
-            let ny = (self.player.y as i32 + dy) as usize;
-            if nx < MAP_SIZE && ny < MAP_SIZE && self.map[ny][nx] == Tile::Floor {
-                if !self.monsters.iter().any(|m| m.x == nx && m.y == ny) {
-                    self.player.path = vec![(nx, ny)];
-                }
-            }
-        }
-
-        // mouse input logic - auto attack on hold
-        if is_mouse_button_down(MouseButton::Left) {
-            let (mx, my) = mouse_position();
-
-            if let Some((tx, ty)) = to_tile(mx, my, self.cam) {
-                // Check if clicked on a monster - attack it directly
-                if let Some(i) = self.monsters.iter().position(|m| m.x == tx && m.y == ty) {
-                    let killed = self.damage_monster(i, self.player.damage());
-                    events.monster_hit = true;
-                    if killed {
-                        events.monster_killed = true;
-                    }
-                    self.player.path.clear();
-                }
-                // Otherwise move to the clicked tile
-                else if self.map[ty][tx] == Tile::Floor {
-                    self.player.path = bfs(&self.map, (self.player.x, self.player.y), (tx, ty))
-                }
-            }
+        for command in commands {
+            self.apply_command(*command, &mut events);
         }
 
         // handle movement for the player
@@ -472,21 +350,22 @@ impl Game {
                     self.player.x = nx;
                     self.player.y = ny;
 
-                    // Update fog of war - reveal area around player
-                    for dy in -4i32..=4 {
-                        for dx in -4i32..=4 {
-                            let ex = self.player.x as i32 + dx;
-                            let ey = self.player.y as i32 + dy;
-                            if ex >= 0
-                                && ey >= 0
-                                && (ex as usize) < MAP_SIZE
-                                && (ey as usize) < MAP_SIZE
-                            {
-                                self.explored[ey as usize][ex as usize] = true;
+                    // Stepping up to the stall in the middle of the shop
+                    // room opens the shop; its stock is kept for the rest of
+                    // the floor
+                    if let Some(shop) = self.shop_room() {
+                        if shop.center() == (nx, ny) {
+                            if self.shop_items.is_empty() {
+                                self.shop_items = create_shop_items();
                             }
+                            self.in_shop = true;
+                            self.player.path.clear();
                         }
                     }
 
+                    // Update fog of war - reveal area around player
+                    reveal(&mut self.explored, (nx, ny), 4);
+
                     // collect gold logic
                     if let Some(i) = self
                         .gold
@@ -494,6 +373,7 @@ impl Game {
                         .position(|&g| g == (self.player.x, self.player.y))
                     {
                         self.gold.remove(i);
+                        self.player.gold += GOLD_VALUE;
                         self.score += GOLD_VALUE;
                         self.total_gold += GOLD_VALUE;
                         events.gold_collected = true;
@@ -532,8 +412,14 @@ impl Game {
 
                     // Spike trap damage
                     if self.spikes.contains(&(self.player.x, self.player.y)) {
-                        let damage = 15 - self.player.armor.min(10);
-                        self.player.hp -= damage.max(5);
+                        // Armor blunts spikes, but only up to a point
+                        let hit = Hit::new(DamageSource::Spikes, DamageType::Physical, 15)
+                            .with_modifiers(DamageModifiers {
+                                armor_cap: Some(10),
+                                minimum: 5,
+                                ..Default::default()
+                            });
+                        let damage = self.hit_player(hit);
                         self.shake = 0.5;
                         events.player_hit = true;
 
@@ -582,14 +468,6 @@ impl Game {
             }
         }
 
-        // Camera follow player
-        let target_cam_x =
-            screen_width() / 2. - (self.player.x as f32 - self.player.y as f32) * TILE_WIDTH;
-        let target_cam_y = 100. - (self.player.x as f32 + self.player.y as f32) * TILE_HEIGHT
-            + screen_height() / 2.;
-        self.cam.0 += (target_cam_x - self.cam.0) * 5.0 * dt;
-        self.cam.1 += (target_cam_y - self.cam.1) * 5.0 * dt;
-
         // Monster logic
         // calculate the occupied spots so enemies dont stack
         let occupied: Vec<_> = self
@@ -605,12 +483,17 @@ impl Game {
                 self.monsters[i].cd = self.monsters[i].move_cd;
 
                 let (mx, my) = (self.monsters[i].x, self.monsters[i].y);
-                let monster_damage = self.monsters[i].damage;
 
                 let d = dist((mx, my), (self.player.x, self.player.y));
 
                 if d == 1 {
-                    self.player.hp -= monster_damage;
+                    let monster = &self.monsters[i];
+                    let hit = Hit::from_dealer(
+                        DamageSource::Monster(monster.name.clone()),
+                        DamageType::Physical,
+                        monster,
+                    );
+                    let monster_damage = self.hit_player(hit);
                     self.shake = 1.0; // Screen shake on hit
                     events.player_hit = true;
                     let (sx, sy) = to_screen(self.player.x, self.player.y, self.cam);
@@ -636,8 +519,193 @@ impl Game {
         events
     }
 
-    fn damage_monster(&mut self, idx: usize, amount: i32) -> bool {
-        self.monsters[idx].hp -= amount;
+    /// Ease the camera towards the player for a viewport of the given size.
+    pub fn update_camera(&mut self, dt: f32, viewport: (f32, f32)) {
+        let target_cam_x =
+            viewport.0 / 2. - (self.player.x as f32 - self.player.y as f32) * TILE_WIDTH;
+        let target_cam_y =
+            100. - (self.player.x as f32 + self.player.y as f32) * TILE_HEIGHT + viewport.1 / 2.;
+        self.cam.0 += (target_cam_x - self.cam.0) * 5.0 * dt;
+        self.cam.1 += (target_cam_y - self.cam.1) * 5.0 * dt;
+    }
+
+    fn apply_command(&mut self, command: PlayerCommand, events: &mut GameEvents) {
+        match command {
+            PlayerCommand::Dash => {
+                if self.player.dash_cd > 0. || self.player.path.is_empty() {
+                    return;
+                }
+                self.player.dash_cd = 2.0; // 2 second cooldown
+                for _ in 0..3 {
+                    if self.player.path.is_empty() {
+                        break;
+                    }
+                    let (nx, ny) = self.player.path[0];
+                    if self.monsters.iter().any(|m| m.x == nx && m.y == ny) {
+                        break;
+                    }
+                    self.player.path.remove(0);
+                    self.player.x = nx;
+                    self.player.y = ny;
+                }
+            }
+            // Area attack - damages all adjacent monsters
+            PlayerCommand::Area => {
+                if self.player.area_cd > 0. {
+                    return;
+                }
+                self.player.area_cd = 3.0; // 3 second cooldown
+                let mut killed_any = false;
+                let px = self.player.x as i32;
+                let py = self.player.y as i32;
+
+                // Find all adjacent monsters
+                let adjacent: Vec<usize> = self
+                    .monsters
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, m)| {
+                        let dx = (m.x as i32 - px).abs();
+                        let dy = (m.y as i32 - py).abs();
+                        dx <= 1 && dy <= 1 && (dx + dy) > 0
+                    })
+                    .map(|(i, _)| i)
+                    .collect();
+
+                // Damage them in reverse order to avoid index issues
+                for i in adjacent.into_iter().rev() {
+                    let hit = self.player_hit().with_modifiers(DamageModifiers {
+                        multiplier: 0.5,
+                        ..Default::default()
+                    });
+                    if self.damage_monster(i, &hit) {
+                        killed_any = true;
+                    }
+                }
+                if killed_any {
+                    events.monster_killed = true;
+                }
+                events.monster_hit = true;
+            }
+            PlayerCommand::Heal => {
+                if self.player.heal_cd > 0.
+                    || Damageable::hp(&self.player) >= Damageable::max_hp(&self.player)
+                {
+                    return;
+                }
+                self.player.heal_cd = 5.0; // 5 second cooldown
+                let heal_amount = Damageable::max_hp(&self.player) / 4;
+                Damageable::heal(&mut self.player, heal_amount);
+
+                let (sx, sy) = to_screen(self.player.x, self.player.y, self.cam);
+                self.texts.push(DmgText {
+                    x: sx,
+                    y: sy - 40.,
+                    dmg: -heal_amount,
+                    life: DMG_TEXT_DURATION,
+                });
+            }
+            // Ranged attack - hit the nearest monster in range
+            PlayerCommand::Ranged => {
+                if self.player.ranged_cd > 0. {
+                    return;
+                }
+                let px = self.player.x as i32;
+                let py = self.player.y as i32;
+
+                // Find nearest monster within range 5
+                if let Some((idx, _)) = self
+                    .monsters
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, m)| {
+                        let dx = (m.x as i32 - px).abs();
+                        let dy = (m.y as i32 - py).abs();
+                        dx <= 5 && dy <= 5
+                    })
+                    .min_by_key(|(_, m)| {
+                        let dx = (m.x as i32 - px).abs();
+                        let dy = (m.y as i32 - py).abs();
+                        dx + dy
+                    })
+                {
+                    self.player.ranged_cd = 1.5; // 1.5 second cooldown
+                    if self.damage_monster(idx, &self.player_hit()) {
+                        events.monster_killed = true;
+                    }
+                    events.monster_hit = true;
+                }
+            }
+            PlayerCommand::Move { dx, dy } => {
+                let here = (self.player.x, self.player.y);
+                if let Some((nx, ny)) = self.map.step(here, (dx, dy)) {
+                    if self.map.is_floor(nx, ny)
+                        && !self.monsters.iter().any(|m| m.x == nx && m.y == ny)
+                    {
+                        self.player.path = vec![(nx, ny)];
+                    }
+                }
+            }
+            PlayerCommand::PathTo { x, y } => {
+                if self.map.is_floor(x, y) {
+                    self.player.path = bfs(&self.map, (self.player.x, self.player.y), (x, y))
+                }
+            }
+            PlayerCommand::Attack { x, y } => {
+                if let Some(i) = self.monsters.iter().position(|m| m.x == x && m.y == y) {
+                    let killed = self.damage_monster(i, &self.player_hit());
+                    events.monster_hit = true;
+                    if killed {
+                        events.monster_killed = true;
+                    }
+                    self.player.path.clear();
+                }
+            }
+            PlayerCommand::Descend | PlayerCommand::ShopBuy(_) | PlayerCommand::ShopLeave => {}
+        }
+    }
+
+    fn apply_shop_command(&mut self, command: PlayerCommand) {
+        match command {
+            PlayerCommand::ShopBuy(i) => {
+                // Purchases spend the wallet; the score is never touched
+                if let Some((cost, result)) =
+                    try_purchase(&mut self.shop_items, i, self.player.gold)
+                {
+                    self.player.gold -= cost;
+                    if result.heal_full {
+                        self.player.hp = self.player.max_hp;
+                    }
+                    self.player.max_hp += result.max_hp_bonus;
+                    self.player.hp += result.max_hp_bonus;
+                    self.player.weapon_damage += result.damage_bonus;
+                    self.player.armor += result.armor_bonus;
+                }
+            }
+            PlayerCommand::ShopLeave => {
+                self.in_shop = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolve a hit against the player, remembering the first blow that
+    /// took them to zero HP. Returns the damage taken.
+    fn hit_player(&mut self, hit: Hit) -> i32 {
+        let taken = damage::resolve(&mut self.player, &hit);
+        if self.player.is_dead() && self.death_cause.is_none() {
+            self.death_cause = Some(hit.source);
+        }
+        taken
+    }
+
+    /// A basic attack from the player.
+    fn player_hit(&self) -> Hit {
+        Hit::from_dealer(DamageSource::Player, DamageType::Physical, &self.player)
+    }
+
+    fn damage_monster(&mut self, idx: usize, hit: &Hit) -> bool {
+        let amount = damage::resolve(&mut self.monsters[idx], hit);
 
         // spawn the text
         let (sx, sy) = to_screen(self.monsters[idx].x, self.monsters[idx].y, self.cam);
@@ -650,23 +718,14 @@ impl Game {
         });
 
         // kill logic
-        if self.monsters[idx].hp <= 0 {
-            let monster_type = self.monsters[idx].monster_type;
-            let mx = self.monsters[idx].x;
-            let my = self.monsters[idx].y;
-            self.monsters.remove(idx);
+        if self.monsters[idx].is_dead() {
+            let monster = self.monsters.remove(idx);
             self.score += KILL_BONUS;
             self.total_kills += 1;
             self.total_damage_dealt += amount;
 
-            // XP gain based on monster type
-            let xp_gain = match monster_type {
-                crate::world::entities::MonsterType::Fast => 15,
-                crate::world::entities::MonsterType::Normal => 25,
-                crate::world::entities::MonsterType::Tank => 40,
-                crate::world::entities::MonsterType::Boss => 100,
-            };
-            self.player.xp += xp_gain;
+            // XP gain from the monster's archetype
+            self.player.xp += monster.xp;
 
             // Level up check
             while self.player.xp >= self.player.xp_to_next {
@@ -678,19 +737,14 @@ impl Game {
                 self.player.weapon_damage += 1;
             }
 
-            // Equipment drop chance (20% for normal, 50% for boss)
-            let drop_chance = if monster_type == crate::world::entities::MonsterType::Boss {
-                0.5
-            } else {
-                0.2
-            };
-            if macroquad::rand::gen_range(0., 1.) < drop_chance {
-                let eq_type = match macroquad::rand::gen_range(0, 3) {
+            // Equipment drop chance from the monster's archetype
+            if self.rng.next_f32() < monster.drop_chance {
+                let eq_type = match self.rng.gen_range(0, 3) {
                     0 => EquipmentType::Sword,
                     1 => EquipmentType::Shield,
                     _ => EquipmentType::Ring,
                 };
-                self.equipment_drops.push((mx, my, eq_type));
+                self.equipment_drops.push((monster.x, monster.y, eq_type));
             }
 
             return true;
@@ -699,7 +753,8 @@ impl Game {
         false
     }
 
-    pub fn draw(&self) {
+    /// Draw the dungeon and everything in it, without the HUD.
+    pub fn draw(&self, style: RenderStyle) {
         let render_data = GameRenderData {
             map: &self.map,
             explored: &self.explored,
@@ -713,42 +768,47 @@ impl Game {
             spikes: &self.spikes,
             poison: &self.poison,
             equipment_drops: &self.equipment_drops,
-            score: self.score,
-            floor: self.floor,
-            difficulty: self.difficulty,
-            in_shop: self.in_shop,
-            shop_items: &self.shop_items,
+            shop: self.shop_room(),
+            stairs_sealed: self.stairs_sealed(),
+            style,
         };
         render_game(&render_data);
     }
+}
 
-    pub fn handle_shop_input(&mut self) {
-        if !self.in_shop {
-            return;
-        }
-
-        // Buy items with number keys
-        for (i, key) in [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4]
-            .iter()
-            .enumerate()
-        {
-            if is_key_pressed(*key) {
-                if let Some((cost, result)) = try_purchase(&mut self.shop_items, i, self.score) {
-                    self.score -= cost;
-                    if result.heal_full {
-                        self.player.hp = self.player.max_hp;
-                    }
-                    self.player.max_hp += result.max_hp_bonus;
-                    self.player.hp += result.max_hp_bonus;
-                    self.player.weapon_damage += result.damage_bonus;
-                    self.player.armor += result.armor_bonus;
-                }
-            }
-        }
-
-        // Exit shop
-        if is_key_pressed(KeyCode::Enter) {
-            self.in_shop = false;
-        }
+/// Mark everything within `radius` tiles of `pos` as explored.
+fn reveal(explored: &mut Map<bool>, pos: (usize, usize), radius: i32) {
+    let area: Vec<_> = explored.around(pos, radius).collect();
+    for (x, y) in area {
+        explored.set(x, y, true);
     }
 }
+
+/// Build the monsters for a floor, rolling each archetype from the bestiary.
+fn spawn_monsters(
+    positions: &[(usize, usize)],
+    floor: i32,
+    hp_mult: f32,
+    dmg_mult: f32,
+    rng: &mut Rng,
+) -> Vec<Monster> {
+    let roster = bestiary();
+    let is_boss_floor = floor % BOSS_FLOOR_INTERVAL == 0;
+
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| {
+            // First monster on boss floors is a boss
+            let def = match roster.boss() {
+                Some(boss) if is_boss_floor && i == 0 => boss,
+                _ => roster.roll(floor, rng),
+            };
+            let mut m = Monster::from_def(def, x, y);
+            m.max_hp = (m.max_hp as f32 * hp_mult) as i32;
+            m.hp = m.max_hp;
+            m.damage = (m.damage as f32 * dmg_mult) as i32;
+            m
+        })
+        .collect()
+}
@@ -3,16 +3,30 @@
 //! This module provides:
 //! - [`Game`] - Main game state and logic
 //! - [`Player`] - Player state and abilities
-//! - [`Database`] - Persistence layer
+//! - [`bindings`] - Input actions and rebindable keys
+//! - [`command`] - Player commands fed into the simulation
 //! - [`constants`] - Game configuration constants
+//! - [`damage`] - Damage resolution shared by every hit
+//! - `database` - SQLite storage backend (`sqlite` feature)
+//! - [`replay`] - Command recording and playback
+//! - [`rng`] - Seeded random number generation
+//! - [`settings`] - Player settings and colour palettes
+//! - [`storage`] - Storage trait for scores and saves, and its backends
 //! - [`traits`] - Common behavior traits
 
+pub mod bindings;
+pub mod command;
 pub mod constants;
+pub mod damage;
+#[cfg(feature = "sqlite")]
 pub mod database;
 pub mod game;
 pub mod player;
+pub mod replay;
+pub mod rng;
+pub mod settings;
 pub mod shop;
+pub mod storage;
 pub mod traits;
 
-pub use database::Database;
 pub use game::Game;
@@ -2,9 +2,10 @@
 
 use crate::core::constants::{Difficulty, PLAYER_DAMAGE, PLAYER_START_HP};
 use crate::core::traits::{DamageDealer, Damageable};
+use serde::{Deserialize, Serialize};
 
 /// Represents the player character with all stats and abilities.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     /// X position on the map.
     pub x: usize,
@@ -38,6 +39,9 @@ pub struct Player {
     pub xp_to_next: i32,
     /// Remaining poison duration.
     pub poisoned: f32,
+    /// Gold carried, spent in the shop. Separate from the run's score.
+    #[serde(default)]
+    pub gold: i32,
 }
 
 impl Damageable for Player {
@@ -49,10 +53,13 @@ impl Damageable for Player {
         self.max_hp
     }
 
+    fn armor(&self) -> i32 {
+        self.armor
+    }
+
     fn take_damage(&mut self, amount: i32) -> i32 {
-        let reduced = (amount - self.armor.min(amount - 1)).max(1);
-        self.hp -= reduced;
-        reduced
+        self.hp -= amount;
+        amount
     }
 
     fn heal(&mut self, amount: i32) {
@@ -87,6 +94,7 @@ impl Player {
             level: 1,
             xp_to_next: 100,
             poisoned: 0.,
+            gold: 0,
         }
     }
 }
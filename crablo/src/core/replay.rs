@@ -0,0 +1,114 @@
+//! Input replays.
+//!
+//! A run is fully determined by its seed, difficulty and the commands fed
+//! into each fixed [`SIM_DT`](crate::core::constants::SIM_DT) step, so
+//! recording those commands is enough to re-simulate it exactly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::command::PlayerCommand;
+use crate::core::constants::Difficulty;
+use crate::world::bestiary::bestiary;
+
+/// A command issued on a given simulation frame.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    pub frame: u32,
+    pub command: PlayerCommand,
+}
+
+/// Recorded command log of a run.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// [`Bestiary::fingerprint`](crate::world::bestiary::Bestiary::fingerprint)
+    /// of the roster the run was played with; missing from replays recorded
+    /// before rosters were tracked.
+    #[serde(default)]
+    pub roster: Option<u64>,
+    /// Number of simulation steps recorded so far.
+    pub frames: u32,
+    pub entries: Vec<ReplayEntry>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, difficulty: Difficulty) -> Self {
+        Replay {
+            seed,
+            difficulty,
+            roster: Some(bestiary().fingerprint()),
+            frames: 0,
+            entries: vec![],
+        }
+    }
+
+    /// Record the commands of one simulation step.
+    pub fn record(&mut self, commands: &[PlayerCommand]) {
+        for command in commands {
+            self.entries.push(ReplayEntry {
+                frame: self.frames,
+                command: *command,
+            });
+        }
+        self.frames += 1;
+    }
+
+    /// Whether the installed roster is the one the run was recorded with,
+    /// without which playback would drift from what was played.
+    pub fn matches_roster(&self) -> bool {
+        self.roster
+            .is_none_or(|roster| roster == bestiary().fingerprint())
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Feeds a recorded replay back one frame at a time.
+pub struct ReplayPlayer {
+    replay: Replay,
+    frame: u32,
+    cursor: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            frame: 0,
+            cursor: 0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.replay.frames
+    }
+
+    /// Commands for the next simulation step.
+    pub fn next_commands(&mut self) -> Vec<PlayerCommand> {
+        let mut commands = vec![];
+        while let Some(entry) = self.replay.entries.get(self.cursor) {
+            if entry.frame != self.frame {
+                break;
+            }
+            commands.push(entry.command);
+            self.cursor += 1;
+        }
+        self.frame += 1;
+        commands
+    }
+}
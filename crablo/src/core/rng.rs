@@ -0,0 +1,94 @@
+//! Seeded random number generation.
+//!
+//! Every random roll that shapes a run goes through [`Rng`] so that a run
+//! seed and difficulty always reproduce the same dungeon.
+
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small, serializable SplitMix64 generator.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Create the generator used to build a given floor of a run.
+    pub fn for_floor(seed: u64, floor: i32) -> Self {
+        let mut rng = Rng::new(seed ^ (floor as u64).wrapping_mul(0xD1B5_4A32_D192_ED03));
+        rng.next_u64();
+        rng
+    }
+
+    /// Pick a fresh seed for a new run.
+    pub fn random_seed() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        // Keep seeds short enough to read out and type back in
+        Rng::new(nanos).next_u64() & 0xFFFF_FFFF
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform value in `[low, high)`, mirroring `macroquad::rand::gen_range`.
+    pub fn gen_range<T: RandomRange>(&mut self, low: T, high: T) -> T {
+        T::gen_range(self, low, high)
+    }
+}
+
+/// Types that [`Rng::gen_range`] can produce.
+pub trait RandomRange: Sized {
+    fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self;
+}
+
+macro_rules! impl_random_range_int {
+    ($($t:ty),*) => {
+        $(
+            impl RandomRange for $t {
+                fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+                    if high <= low {
+                        return low;
+                    }
+                    let span = (high as i64 - low as i64) as u64;
+                    (low as i64 + (rng.next_u64() % span) as i64) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_random_range_int!(i32, u8, u32, usize);
+
+impl RandomRange for f32 {
+    fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+        low + (high - low) * rng.next_f32()
+    }
+}
+
+/// Format a seed the way it is shown to players.
+pub fn format_seed(seed: u64) -> String {
+    format!("{:08X}", seed)
+}
+
+/// Parse a seed typed in by a player.
+pub fn parse_seed(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.trim(), 16).ok()
+}
@@ -0,0 +1,183 @@
+//! Player settings, kept in a TOML file in the config directory.
+//!
+//! Missing or unknown keys fall back to their defaults, so an old or
+//! hand-edited file never stops the game from starting.
+
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::bindings::Bindings;
+
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+/// Environment variable overriding where settings are kept.
+pub const SETTINGS_PATH_ENV: &str = "CRABLO_SETTINGS_PATH";
+
+/// Smallest and largest UI scale the player can pick.
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 2.;
+
+/// Everything the player can change from the Settings screen.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Scales every other volume, 0 to 1.
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    /// Only read when the window is created.
+    pub vsync: bool,
+    /// UI scale on top of fitting the window, see `ui::layout`.
+    pub ui_scale: f32,
+    /// Screen-shake intensity, 0 turns it off.
+    pub screen_shake: f32,
+    pub palette: Palette,
+    /// Keys for every action, see the controls screen.
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.,
+            sfx_volume: 1.,
+            fullscreen: false,
+            vsync: true,
+            ui_scale: 1.,
+            screen_shake: 1.,
+            palette: Palette::Classic,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Settings file location - `settings.toml` under the platform config
+    /// directory, or `CRABLO_SETTINGS_PATH` when set.
+    pub fn default_path() -> PathBuf {
+        if let Some(path) = std::env::var_os(SETTINGS_PATH_ENV) {
+            return PathBuf::from(path);
+        }
+        dirs::config_dir()
+            .map(|dir| dir.join("crablo"))
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(SETTINGS_FILE_NAME)
+    }
+
+    /// Parse settings, pulling out-of-range values back into range.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let settings: Settings = toml::from_str(text).map_err(|e| e.to_string())?;
+        Ok(settings.clamped())
+    }
+
+    /// Load settings from `path`, or the defaults when there's no file yet.
+    /// A file that can't be read or parsed is an error, so the player can be
+    /// told why their settings didn't apply.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// Write settings to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let text = toml::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    /// The same settings with every number in its allowed range.
+    pub fn clamped(self) -> Self {
+        Settings {
+            master_volume: self.master_volume.clamp(0., 1.),
+            sfx_volume: self.sfx_volume.clamp(0., 1.),
+            ui_scale: self.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE),
+            screen_shake: self.screen_shake.clamp(0., 1.),
+            ..self
+        }
+    }
+}
+
+/// Colour scheme for things told apart by colour alone.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    Classic,
+    HighContrast,
+    /// Avoids red/green pairs, for the common forms of colour blindness.
+    ColorBlind,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Classic, Palette::HighContrast, Palette::ColorBlind];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Classic => "Classic",
+            Palette::HighContrast => "High contrast",
+            Palette::ColorBlind => "Colour-blind",
+        }
+    }
+
+    pub fn colors(self) -> Colors {
+        match self {
+            Palette::Classic => Colors {
+                player: BLACK,
+                gold: GOLD,
+                potion: RED,
+                poison: Color::new(0.2, 0.8, 0.2, 0.7),
+                path: GOLD,
+                player_health: RED,
+                monster_health: GREEN,
+                damage: RED,
+                heal: GREEN,
+            },
+            Palette::HighContrast => Colors {
+                player: Color::new(0., 0., 0.8, 1.),
+                gold: YELLOW,
+                potion: MAGENTA,
+                poison: Color::new(0., 0.9, 0., 0.9),
+                path: Color::new(0., 0.8, 0.8, 1.),
+                player_health: Color::new(0.9, 0., 0., 1.),
+                monster_health: Color::new(0., 0.9, 0., 1.),
+                damage: Color::new(0.9, 0., 0., 1.),
+                heal: Color::new(0., 0.6, 0., 1.),
+            },
+            // Okabe-Ito colours
+            Palette::ColorBlind => Colors {
+                player: BLACK,
+                gold: Color::new(0.94, 0.89, 0.26, 1.),
+                potion: Color::new(0.84, 0.37, 0., 1.),
+                poison: Color::new(0.8, 0.47, 0.65, 0.8),
+                path: Color::new(0.34, 0.71, 0.91, 1.),
+                player_health: Color::new(0.84, 0.37, 0., 1.),
+                monster_health: Color::new(0., 0.45, 0.7, 1.),
+                damage: Color::new(0.84, 0.37, 0., 1.),
+                heal: Color::new(0., 0.45, 0.7, 1.),
+            },
+        }
+    }
+}
+
+/// Colours picked by a [`Palette`].
+#[derive(Clone, Copy)]
+pub struct Colors {
+    pub player: Color,
+    pub gold: Color,
+    pub potion: Color,
+    pub poison: Color,
+    /// Markers along the player's planned path.
+    pub path: Color,
+    pub player_health: Color,
+    pub monster_health: Color,
+    /// Floating damage numbers.
+    pub damage: Color,
+    /// Floating heal numbers.
+    pub heal: Color,
+}
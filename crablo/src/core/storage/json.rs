@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use super::memory::Records;
+use super::{
+    MemoryStorage, Result, RunStats, SaveData, SaveSlot, ScoreEntry, Storage, StorageError,
+};
+use crate::core::constants::Difficulty;
+
+/// Format version written by this build. Version 2 added save slots.
+const FORMAT_VERSION: i32 = 2;
+
+/// Keeps everything in a single JSON file, rewritten after every change.
+/// Useful where bundled SQLite is unwanted.
+pub struct JsonStorage {
+    path: PathBuf,
+    memory: MemoryStorage,
+}
+
+impl JsonStorage {
+    /// Open the file at `path`, starting empty if it doesn't exist yet.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut records: Records = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Records::default(),
+            Err(e) => return Err(e.into()),
+        };
+        if records.version > FORMAT_VERSION {
+            return Err(StorageError::NewerVersion {
+                found: records.version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        records.upgrade_single_save();
+        records.version = FORMAT_VERSION;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(JsonStorage {
+            path: path.to_path_buf(),
+            memory: MemoryStorage { records },
+        })
+    }
+
+    /// Write the file next to its final location and swap it in, so a crash
+    /// mid-write never leaves a truncated file behind.
+    fn flush(&self) -> Result<()> {
+        let json = serde_json::to_string(&self.memory.records)?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Storage for JsonStorage {
+    fn save_score(
+        &mut self,
+        name: &str,
+        score: i32,
+        seed: u64,
+        replay: &str,
+        stats: &RunStats,
+    ) -> Result<()> {
+        self.memory.save_score(name, score, seed, replay, stats)?;
+        self.flush()
+    }
+
+    fn get_top_scores(
+        &self,
+        limit: i32,
+        difficulty: Option<Difficulty>,
+    ) -> Result<Vec<ScoreEntry>> {
+        self.memory.get_top_scores(limit, difficulty)
+    }
+
+    fn load_replay(&self, score_id: i64) -> Result<Option<String>> {
+        self.memory.load_replay(score_id)
+    }
+
+    fn reset_scores(&mut self) -> Result<()> {
+        self.memory.reset_scores()?;
+        self.flush()
+    }
+
+    fn save_game(&mut self, slot: i32, save: &SaveData) -> Result<()> {
+        self.memory.save_game(slot, save)?;
+        self.flush()
+    }
+
+    fn load_game(&self, slot: i32) -> Result<Option<SaveData>> {
+        self.memory.load_game(slot)
+    }
+
+    fn delete_save(&mut self, slot: i32) -> Result<()> {
+        self.memory.delete_save(slot)?;
+        self.flush()
+    }
+
+    fn list_saves(&self) -> Result<Vec<SaveSlot>> {
+        self.memory.list_saves()
+    }
+
+    fn begin_session(&mut self) -> Result<bool> {
+        let interrupted = self.memory.begin_session()?;
+        self.flush()?;
+        Ok(interrupted)
+    }
+
+    fn end_session(&mut self) -> Result<()> {
+        self.memory.end_session()?;
+        self.flush()
+    }
+}
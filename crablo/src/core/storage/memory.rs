@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use super::{timestamp_now, Result, RunStats, SaveData, SaveSlot, ScoreEntry, Storage};
+use crate::core::constants::Difficulty;
+
+/// A score as kept by the in-memory and JSON backends.
+#[derive(Clone, Serialize, Deserialize)]
+struct StoredScore {
+    id: i64,
+    name: String,
+    score: i32,
+    seed: u64,
+    replay: Option<String>,
+    stats: RunStats,
+    created_at: String,
+}
+
+/// A save slot as kept by the in-memory and JSON backends.
+#[derive(Clone, Serialize, Deserialize)]
+struct StoredSave {
+    save: SaveData,
+    saved_at: String,
+}
+
+/// Everything the non-SQL backends keep, in the shape written to disk.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(super) struct Records {
+    /// File format version, checked by the JSON backend.
+    #[serde(default)]
+    pub(super) version: i32,
+    next_id: i64,
+    scores: Vec<StoredScore>,
+    #[serde(default)]
+    saves: BTreeMap<i32, StoredSave>,
+    /// Set while a game session is running.
+    #[serde(default)]
+    session_open: bool,
+    /// The single save of format version 1, moved into slot 1 on load.
+    #[serde(default, skip_serializing)]
+    save: Option<SaveData>,
+}
+
+impl Records {
+    /// Move a version 1 single save into slot 1.
+    pub(super) fn upgrade_single_save(&mut self) {
+        if let Some(save) = self.save.take() {
+            self.saves.entry(1).or_insert(StoredSave {
+                save,
+                saved_at: String::new(),
+            });
+        }
+    }
+}
+
+/// Keeps scores and the saved run for the lifetime of the process only.
+#[derive(Default)]
+pub struct MemoryStorage {
+    pub(super) records: Records,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn save_score(
+        &mut self,
+        name: &str,
+        score: i32,
+        seed: u64,
+        replay: &str,
+        stats: &RunStats,
+    ) -> Result<()> {
+        let records = &mut self.records;
+        records.next_id += 1;
+        records.scores.push(StoredScore {
+            id: records.next_id,
+            name: name.to_string(),
+            score,
+            seed,
+            replay: Some(replay.to_string()),
+            stats: stats.clone(),
+            created_at: timestamp_now(),
+        });
+        Ok(())
+    }
+
+    fn get_top_scores(
+        &self,
+        limit: i32,
+        difficulty: Option<Difficulty>,
+    ) -> Result<Vec<ScoreEntry>> {
+        let mut scores: Vec<&StoredScore> = self
+            .records
+            .scores
+            .iter()
+            .filter(|s| difficulty.is_none() || s.stats.difficulty == difficulty)
+            .collect();
+        scores.sort_by_key(|s| Reverse(s.score));
+
+        Ok(scores
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .enumerate()
+            .map(|(i, s)| ScoreEntry {
+                id: s.id,
+                rank: (i + 1) as i32,
+                name: s.name.clone(),
+                score: s.score,
+                seed: Some(s.seed),
+                has_replay: s.replay.is_some(),
+                stats: s.stats.clone(),
+                created_at: s.created_at.clone(),
+            })
+            .collect())
+    }
+
+    fn load_replay(&self, score_id: i64) -> Result<Option<String>> {
+        Ok(self
+            .records
+            .scores
+            .iter()
+            .find(|s| s.id == score_id)
+            .and_then(|s| s.replay.clone()))
+    }
+
+    fn reset_scores(&mut self) -> Result<()> {
+        self.records.scores.clear();
+        Ok(())
+    }
+
+    fn save_game(&mut self, slot: i32, save: &SaveData) -> Result<()> {
+        self.records.saves.insert(
+            slot,
+            StoredSave {
+                save: save.clone(),
+                saved_at: timestamp_now(),
+            },
+        );
+        Ok(())
+    }
+
+    fn load_game(&self, slot: i32) -> Result<Option<SaveData>> {
+        Ok(self.records.saves.get(&slot).map(|s| s.save.clone()))
+    }
+
+    fn delete_save(&mut self, slot: i32) -> Result<()> {
+        self.records.saves.remove(&slot);
+        Ok(())
+    }
+
+    fn list_saves(&self) -> Result<Vec<SaveSlot>> {
+        Ok(self
+            .records
+            .saves
+            .iter()
+            .map(|(&slot, stored)| SaveSlot {
+                slot,
+                name: stored.save.name.clone(),
+                floor: stored.save.floor,
+                level: stored.save.level,
+                gold: stored.save.gold,
+                difficulty: Difficulty::from_id(stored.save.difficulty),
+                playtime: stored.save.playtime,
+                saved_at: stored.saved_at.clone(),
+            })
+            .collect())
+    }
+
+    fn begin_session(&mut self) -> Result<bool> {
+        Ok(std::mem::replace(&mut self.records.session_open, true))
+    }
+
+    fn end_session(&mut self) -> Result<()> {
+        self.records.session_open = false;
+        Ok(())
+    }
+}
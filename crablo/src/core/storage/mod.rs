@@ -0,0 +1,319 @@
+//! Persistence of scores and saved runs.
+//!
+//! The game only talks to the [`Storage`] trait. The backend is picked at
+//! startup: SQLite ([`Database`](crate::core::database::Database), behind the
+//! default `sqlite` feature), a single JSON file, or memory only.
+
+mod json;
+mod memory;
+
+pub use json::JsonStorage;
+pub use memory::MemoryStorage;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::constants::Difficulty;
+#[cfg(feature = "sqlite")]
+use crate::core::database::Database;
+
+/// Environment variable overriding where the backend keeps its file.
+pub const STORAGE_PATH_ENV: &str = "CRABLO_DB_PATH";
+
+/// Number of save slots offered in the slot picker.
+pub const SAVE_SLOTS: i32 = 5;
+
+/// Slot the running game is autosaved into, apart from the named slots.
+pub const AUTOSAVE_SLOT: i32 = 0;
+
+/// Environment variable picking the backend (`sqlite`, `json` or `memory`).
+pub const STORAGE_BACKEND_ENV: &str = "CRABLO_STORAGE";
+
+/// How a finished run went, stored alongside its score. Entries saved before
+/// these were recorded read back with no difficulty and zeroed counters.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    pub difficulty: Option<Difficulty>,
+    pub floor: i32,
+    pub level: i32,
+    pub kills: i32,
+    pub gold: i32,
+    pub damage_dealt: i32,
+    /// Length of the run in seconds.
+    pub duration: i32,
+    /// What killed the player; `None` for wins.
+    pub cause: Option<String>,
+    /// Whether the run cleared the final floor.
+    pub won: bool,
+}
+
+#[derive(Clone)]
+pub struct ScoreEntry {
+    pub id: i64,
+    pub rank: i32,
+    pub name: String,
+    pub score: i32,
+    /// Seed of the run, if it was recorded.
+    pub seed: Option<u64>,
+    /// Whether an input replay was stored with the score.
+    pub has_replay: bool,
+    pub stats: RunStats,
+    pub created_at: String,
+}
+
+/// A saved run. The summary fields are kept for display, while `snapshot`
+/// holds the serialized [`Game`](crate::core::Game) that is restored on load.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    /// Name shown in the slot picker.
+    #[serde(default)]
+    pub name: String,
+    pub floor: i32,
+    #[serde(default)]
+    pub level: i32,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub score: i32,
+    pub gold: i32,
+    pub difficulty: i32,
+    /// Seconds played so far.
+    #[serde(default)]
+    pub playtime: i32,
+    pub snapshot: String,
+}
+
+/// What the slot picker shows for a filled slot, without the snapshot.
+pub struct SaveSlot {
+    pub slot: i32,
+    pub name: String,
+    pub floor: i32,
+    pub level: i32,
+    pub gold: i32,
+    pub difficulty: Option<Difficulty>,
+    pub playtime: i32,
+    /// When the slot was last written, as `YYYY-MM-DD HH:MM:SS` UTC.
+    pub saved_at: String,
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The file was written by a newer build of the game.
+    NewerVersion {
+        found: i32,
+        supported: i32,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "sqlite")]
+            StorageError::Sqlite(e) => write!(f, "{}", e),
+            StorageError::Io(e) => write!(f, "{}", e),
+            StorageError::Json(e) => write!(f, "{}", e),
+            StorageError::NewerVersion { found, supported } => write!(
+                f,
+                "schema version {} is newer than the supported version {}",
+                found, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for StorageError {
+    fn from(e: rusqlite::Error) -> Self {
+        StorageError::Sqlite(e)
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(e: serde_json::Error) -> Self {
+        StorageError::Json(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// Where scores and the saved run are kept.
+pub trait Storage {
+    fn save_score(
+        &mut self,
+        name: &str,
+        score: i32,
+        seed: u64,
+        replay: &str,
+        stats: &RunStats,
+    ) -> Result<()>;
+
+    /// Best scores, optionally only those played on `difficulty`.
+    fn get_top_scores(&self, limit: i32, difficulty: Option<Difficulty>)
+        -> Result<Vec<ScoreEntry>>;
+
+    /// Fetch the input replay recorded with a score, if any.
+    fn load_replay(&self, score_id: i64) -> Result<Option<String>>;
+
+    fn reset_scores(&mut self) -> Result<()>;
+
+    /// Write `save` into `slot`, replacing whatever was there.
+    fn save_game(&mut self, slot: i32, save: &SaveData) -> Result<()>;
+
+    fn load_game(&self, slot: i32) -> Result<Option<SaveData>>;
+
+    fn delete_save(&mut self, slot: i32) -> Result<()>;
+
+    /// Filled slots that can be resumed, in slot order.
+    fn list_saves(&self) -> Result<Vec<SaveSlot>>;
+
+    fn has_save(&self) -> Result<bool> {
+        Ok(!self.list_saves()?.is_empty())
+    }
+
+    /// Mark a session as running. Returns whether the previous one never
+    /// reached [`end_session`](Storage::end_session), i.e. the game crashed
+    /// or was killed.
+    fn begin_session(&mut self) -> Result<bool>;
+
+    /// Mark the running session as cleanly closed.
+    fn end_session(&mut self) -> Result<()>;
+}
+
+/// Storage backends selectable at startup.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+    Json,
+    Memory,
+}
+
+impl Backend {
+    /// Backend called `name`, ignoring case.
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(Backend::Sqlite),
+            "json" => Ok(Backend::Json),
+            "memory" => Ok(Backend::Memory),
+            _ => Err(format!("unknown storage backend '{}'", name)),
+        }
+    }
+
+    /// Backend named by `$CRABLO_STORAGE`, or the build's default when it
+    /// isn't set.
+    pub fn from_env() -> std::result::Result<Self, String> {
+        match std::env::var(STORAGE_BACKEND_ENV) {
+            Ok(name) => {
+                Backend::parse(&name).map_err(|e| format!("{}: {}", STORAGE_BACKEND_ENV, e))
+            }
+            Err(_) => Ok(Backend::default()),
+        }
+    }
+
+    /// Where this backend keeps its data unless told otherwise:
+    /// `$CRABLO_DB_PATH` if set, otherwise the platform data directory
+    /// (`$XDG_DATA_HOME/crablo` on Linux). `None` for memory storage.
+    pub fn default_path(&self) -> Option<PathBuf> {
+        if *self == Backend::Memory {
+            return None;
+        }
+        if let Some(path) = std::env::var_os(STORAGE_PATH_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        Some(match self {
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Database::default_path(),
+            _ => data_dir().join("crablo_data.json"),
+        })
+    }
+
+    /// Open the backend, keeping its data at `path` when it uses a file.
+    pub fn open(&self, path: &Path) -> Result<Box<dyn Storage>> {
+        Ok(match self {
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => {
+                if path == Database::default_path() {
+                    Database::adopt_legacy(path)?;
+                }
+                Box::new(Database::open(path)?)
+            }
+            Backend::Json => Box::new(JsonStorage::open(path)?),
+            Backend::Memory => Box::new(MemoryStorage::new()),
+        })
+    }
+}
+
+impl Default for Backend {
+    #[cfg(feature = "sqlite")]
+    fn default() -> Self {
+        Backend::Sqlite
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    fn default() -> Self {
+        Backend::Json
+    }
+}
+
+/// Mark a session as running on `storage` and return the autosave the
+/// previous session left behind if it never closed cleanly.
+pub fn interrupted_run(storage: &mut dyn Storage) -> Option<SaveSlot> {
+    let interrupted = storage.begin_session().unwrap_or(false);
+    storage
+        .list_saves()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|s| s.slot == AUTOSAVE_SLOT)
+        .filter(|_| interrupted)
+}
+
+/// Per-user directory for crablo's data files.
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("crablo"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Current UTC time in SQLite's `CURRENT_TIMESTAMP` format.
+fn timestamp_now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
@@ -10,7 +10,14 @@ pub trait Damageable {
     /// Get maximum health points.
     fn max_hp(&self) -> i32;
 
-    /// Take damage and return actual damage taken.
+    /// Armor counted against physical hits.
+    fn armor(&self) -> i32 {
+        0
+    }
+
+    /// Take already-resolved damage and return the amount actually taken.
+    /// Hits should go through [`damage::resolve`](crate::core::damage::resolve)
+    /// rather than calling this directly.
     fn take_damage(&mut self, amount: i32) -> i32;
 
     /// Check if the entity is dead.
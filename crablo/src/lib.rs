@@ -6,8 +6,10 @@
 //!
 //! - [`core`] - Core game logic, state management, and configuration
 //! - [`systems`] - Game systems (rendering, audio, pathfinding)
+//! - [`ui`] - Screens and overlays on a scene stack
 //! - [`world`] - World entities and map generation
 
 pub mod core;
 pub mod systems;
+pub mod ui;
 pub mod world;
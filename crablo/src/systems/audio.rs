@@ -13,6 +13,8 @@ pub struct GameAudio {
     pub death_sound: Option<Sound>,
     pub level_sound: Option<Sound>,
     pub game_over_sound: Option<Sound>,
+    /// Master times effects volume, scaling each sound's own level.
+    sfx_volume: f32,
 }
 
 impl GameAudio {
@@ -30,66 +32,44 @@ impl GameAudio {
             death_sound,
             level_sound,
             game_over_sound,
+            sfx_volume: 1.,
         }
     }
 
-    pub fn play_hit(&self) {
-        if let Some(sound) = &self.hit_sound {
+    /// Apply the volume settings to every sound played from now on.
+    pub fn set_volumes(&mut self, master: f32, sfx: f32) {
+        self.sfx_volume = master * sfx;
+    }
+
+    fn play(&self, sound: &Option<Sound>, volume: f32) {
+        if let Some(sound) = sound {
             play_sound(
                 sound,
                 PlaySoundParams {
                     looped: false,
-                    volume: 0.5,
+                    volume: volume * self.sfx_volume,
                 },
             );
         }
     }
 
+    pub fn play_hit(&self) {
+        self.play(&self.hit_sound, 0.5);
+    }
+
     pub fn play_gold(&self) {
-        if let Some(sound) = &self.gold_sound {
-            play_sound(
-                sound,
-                PlaySoundParams {
-                    looped: false,
-                    volume: 0.6,
-                },
-            );
-        }
+        self.play(&self.gold_sound, 0.6);
     }
 
     pub fn play_death(&self) {
-        if let Some(sound) = &self.death_sound {
-            play_sound(
-                sound,
-                PlaySoundParams {
-                    looped: false,
-                    volume: 0.7,
-                },
-            );
-        }
+        self.play(&self.death_sound, 0.7);
     }
 
     pub fn play_level_complete(&self) {
-        if let Some(sound) = &self.level_sound {
-            play_sound(
-                sound,
-                PlaySoundParams {
-                    looped: false,
-                    volume: 0.8,
-                },
-            );
-        }
+        self.play(&self.level_sound, 0.8);
     }
 
     pub fn play_game_over(&self) {
-        if let Some(sound) = &self.game_over_sound {
-            play_sound(
-                sound,
-                PlaySoundParams {
-                    looped: false,
-                    volume: 0.8,
-                },
-            );
-        }
+        self.play(&self.game_over_sound, 0.8);
     }
 }
@@ -1,14 +1,27 @@
 use macroquad::prelude::*;
 
-use crate::core::constants::{Difficulty, MAP_SIZE, MONSTER_HP};
 use crate::core::player::Player;
-use crate::systems::rendering::{draw_stickman, draw_stickman_typed, draw_wall, to_screen};
-use crate::world::entities::{DmgText, EquipmentType, Monster, MonsterType, ShopItem};
-use crate::world::map::Tile;
+use crate::core::settings::Colors;
+use crate::core::traits::Damageable;
+use crate::systems::rendering::{draw_stickman_styled, draw_wall, to_screen};
+use crate::world::entities::{DmgText, EquipmentType, Monster};
+use crate::world::map::{Map, Room, Tile};
+
+/// Player settings that change how the world is drawn.
+#[derive(Clone, Copy)]
+pub struct RenderStyle {
+    /// UI scale applied to floating damage numbers.
+    pub text_scale: f32,
+    /// Screen-shake intensity, 0 to 1.
+    pub shake: f32,
+    pub colors: Colors,
+}
 
+/// What the world renderer needs from a run. The HUD and screens on top of
+/// it are drawn by [`crate::ui`].
 pub struct GameRenderData<'a> {
-    pub map: &'a [[Tile; MAP_SIZE]; MAP_SIZE],
-    pub explored: &'a [[bool; MAP_SIZE]; MAP_SIZE],
+    pub map: &'a Map,
+    pub explored: &'a Map<bool>,
     pub cam: (f32, f32),
     pub shake: f32,
     pub player: &'a Player,
@@ -19,28 +32,22 @@ pub struct GameRenderData<'a> {
     pub spikes: &'a [(usize, usize)],
     pub poison: &'a [(usize, usize)],
     pub equipment_drops: &'a [(usize, usize, EquipmentType)],
-    pub score: i32,
-    pub floor: i32,
-    pub difficulty: Difficulty,
-    pub in_shop: bool,
-    pub shop_items: &'a [ShopItem],
+    /// The shop room, marked by a stall in its middle.
+    pub shop: Option<&'a Room>,
+    /// Whether the stairs down are barred.
+    pub stairs_sealed: bool,
+    pub style: RenderStyle,
 }
 
 pub fn render_game(data: &GameRenderData) {
-    let cam_with_shake = apply_screen_shake(data.cam, data.shake);
+    let cam_with_shake = apply_screen_shake(data.cam, data.shake * data.style.shake);
 
     render_map(data, cam_with_shake);
     render_equipment_drops(data, cam_with_shake);
     render_path(data, cam_with_shake);
     render_player(data, cam_with_shake);
     render_monsters(data, cam_with_shake);
-    render_floating_texts(data.texts);
-    render_hud(data);
-    render_minimap(data);
-
-    if data.in_shop {
-        render_shop(data.shop_items, data.score);
-    }
+    render_floating_texts(data.texts, &data.style);
 }
 
 fn apply_screen_shake(cam: (f32, f32), shake: f32) -> (f32, f32) {
@@ -55,27 +62,31 @@ fn apply_screen_shake(cam: (f32, f32), shake: f32) -> (f32, f32) {
 }
 
 fn render_map(data: &GameRenderData, cam: (f32, f32)) {
-    for y in 0..MAP_SIZE {
-        for x in 0..MAP_SIZE {
-            if !data.explored[y][x] {
-                continue;
-            }
+    for (x, y) in data.map.positions() {
+        if !is_explored(data, x, y) {
+            continue;
+        }
 
-            if data.map[y][x] == Tile::Wall {
-                draw_wall(x, y, cam);
-            } else {
-                let (sx, sy) = to_screen(x, y, cam);
-                render_tile_content(data, x, y, sx, sy);
-            }
+        if data.map.get(x, y) == Some(Tile::Wall) {
+            draw_wall(x, y, cam);
+        } else {
+            let (sx, sy) = to_screen(x, y, cam);
+            render_tile_content(data, x, y, sx, sy);
         }
     }
 }
 
+fn is_explored(data: &GameRenderData, x: usize, y: usize) -> bool {
+    data.explored.get(x, y) == Some(true)
+}
+
 fn render_tile_content(data: &GameRenderData, x: usize, y: usize, sx: f32, sy: f32) {
-    if data.gold.contains(&(x, y)) {
-        draw_circle(sx, sy + 16., 6., GOLD);
+    if data.map.get(x, y) == Some(Tile::StairsDown) {
+        render_stairs(sx, sy, data.stairs_sealed);
+    } else if data.gold.contains(&(x, y)) {
+        draw_circle(sx, sy + 16., 6., data.style.colors.gold);
     } else if data.potions.contains(&(x, y)) {
-        draw_circle(sx, sy + 12., 8., RED);
+        draw_circle(sx, sy + 12., 8., data.style.colors.potion);
         draw_line(sx - 4., sy + 12., sx + 4., sy + 12., 2., WHITE);
         draw_line(sx, sy + 8., sx, sy + 16., 2., WHITE);
     } else if data.spikes.contains(&(x, y)) {
@@ -86,15 +97,32 @@ fn render_tile_content(data: &GameRenderData, x: usize, y: usize, sx: f32, sy: f
             DARKGRAY,
         );
     } else if data.poison.contains(&(x, y)) {
-        draw_ellipse(sx, sy + 16., 8., 4., 0., Color::new(0.2, 0.8, 0.2, 0.7));
+        draw_ellipse(sx, sy + 16., 8., 4., 0., data.style.colors.poison);
+    } else if data.shop.map(|room| room.center()) == Some((x, y)) {
+        draw_rectangle(sx - 9., sy + 6., 18., 12., BROWN);
+        draw_rectangle_lines(sx - 9., sy + 6., 18., 12., 2., data.style.colors.gold);
+        draw_text("$", sx - 4., sy + 17., 16., data.style.colors.gold);
     } else {
         draw_circle(sx, sy + 16., 2., LIGHTGRAY);
     }
 }
 
+/// Steps going down into the tile, barred while sealed.
+fn render_stairs(sx: f32, sy: f32, sealed: bool) {
+    for i in 0..3 {
+        let step = i as f32;
+        let half = 12. - step * 3.;
+        draw_rectangle(sx - half, sy + 8. + step * 4., half * 2., 3., GRAY);
+    }
+    if sealed {
+        draw_line(sx - 10., sy + 6., sx + 10., sy + 20., 3., MAROON);
+        draw_line(sx + 10., sy + 6., sx - 10., sy + 20., 3., MAROON);
+    }
+}
+
 fn render_equipment_drops(data: &GameRenderData, cam: (f32, f32)) {
     for (ex, ey, eq_type) in data.equipment_drops {
-        if data.explored[*ey][*ex] {
+        if is_explored(data, *ex, *ey) {
             let (sx, sy) = to_screen(*ex, *ey, cam);
             let color = match eq_type {
                 EquipmentType::Sword => ORANGE,
@@ -109,33 +137,41 @@ fn render_equipment_drops(data: &GameRenderData, cam: (f32, f32)) {
 
 fn render_path(data: &GameRenderData, cam: (f32, f32)) {
     for (px, py) in &data.player.path {
-        if data.explored[*py][*px] {
+        if is_explored(data, *px, *py) {
             let (sx, sy) = to_screen(*px, *py, cam);
-            draw_circle(sx, sy + 16., 4., GOLD);
+            draw_circle(sx, sy + 16., 4., data.style.colors.path);
         }
     }
 }
 
 fn render_player(data: &GameRenderData, cam: (f32, f32)) {
-    draw_stickman(data.player.x, data.player.y, cam, false);
+    draw_stickman_styled(
+        data.player.x,
+        data.player.y,
+        cam,
+        false,
+        data.style.colors.player,
+        1.0,
+    );
 }
 
 fn render_monsters(data: &GameRenderData, cam: (f32, f32)) {
     for m in data.monsters {
-        if !data.explored[m.y][m.x] {
+        if !is_explored(data, m.x, m.y) {
             continue;
         }
-        draw_stickman_typed(m.x, m.y, cam, true, Some(m.monster_type));
-        render_monster_health_bar(m, cam, data.difficulty);
+        let [r, g, b] = m.color;
+        draw_stickman_styled(m.x, m.y, cam, true, Color::from_rgba(r, g, b, 255), m.scale);
+        let (sx, sy) = to_screen(m.x, m.y, cam);
+        render_health_bar(m, sx, sy, data.style.colors.monster_health);
     }
 }
 
-fn render_monster_health_bar(m: &Monster, cam: (f32, f32), difficulty: Difficulty) {
-    let (sx, sy) = to_screen(m.x, m.y, cam);
+/// Small health bar floating above anything that can take damage.
+fn render_health_bar(target: &impl Damageable, sx: f32, sy: f32, color: Color) {
     let bar_width = 24.;
     let bar_height = 4.;
-    let max_hp = get_monster_max_hp(m.monster_type) * difficulty.monster_hp_mult();
-    let hp_ratio = m.hp as f32 / max_hp;
+    let hp_ratio = (target.hp() as f32 / target.max_hp().max(1) as f32).clamp(0., 1.);
 
     draw_rectangle(
         sx - bar_width / 2.,
@@ -149,252 +185,17 @@ fn render_monster_health_bar(m: &Monster, cam: (f32, f32), difficulty: Difficult
         sy - 45.,
         bar_width * hp_ratio,
         bar_height,
-        GREEN,
+        color,
     );
 }
 
-fn get_monster_max_hp(monster_type: MonsterType) -> f32 {
-    match monster_type {
-        MonsterType::Fast => (MONSTER_HP / 2) as f32,
-        MonsterType::Tank => (MONSTER_HP * 2) as f32,
-        MonsterType::Normal => MONSTER_HP as f32,
-        MonsterType::Boss => (MONSTER_HP * 5) as f32,
-    }
-}
-
-fn render_floating_texts(texts: &[DmgText]) {
+fn render_floating_texts(texts: &[DmgText], style: &RenderStyle) {
     for t in texts {
         let (text, color) = if t.dmg < 0 {
-            (format!("+{}", -t.dmg), GREEN)
+            (format!("+{}", -t.dmg), style.colors.heal)
         } else {
-            (format!("-{}", t.dmg), RED)
+            (format!("-{}", t.dmg), style.colors.damage)
         };
-        draw_text(&text, t.x, t.y, 20., color);
+        draw_text(&text, t.x, t.y, 20. * style.text_scale, color);
     }
 }
-
-fn render_hud(data: &GameRenderData) {
-    render_hp_bar(data.player);
-    render_score_floor(data.score, data.floor);
-    render_difficulty(data.difficulty);
-    render_ability_cooldowns(data.player);
-    render_level_xp(data.player);
-    render_equipment_stats(data.player);
-    render_poison_indicator(data.player);
-}
-
-fn render_hp_bar(player: &Player) {
-    let hp_bar_width = 200.;
-    let hp_bar_height = 20.;
-    let hp_ratio = player.hp as f32 / player.max_hp as f32;
-
-    draw_rectangle(
-        20.,
-        screen_height() - 50.,
-        hp_bar_width,
-        hp_bar_height,
-        DARKGRAY,
-    );
-    draw_rectangle(
-        20.,
-        screen_height() - 50.,
-        hp_bar_width * hp_ratio,
-        hp_bar_height,
-        RED,
-    );
-    draw_rectangle_lines(
-        20.,
-        screen_height() - 50.,
-        hp_bar_width,
-        hp_bar_height,
-        2.,
-        BLACK,
-    );
-    draw_text(
-        &format!("{}/{}", player.hp, player.max_hp),
-        25.,
-        screen_height() - 35.,
-        18.,
-        WHITE,
-    );
-}
-
-fn render_score_floor(score: i32, floor: i32) {
-    draw_text(
-        &format!("SCORE: {}  FLOOR: {}", score, floor),
-        20.,
-        screen_height() - 70.,
-        24.,
-        BLACK,
-    );
-}
-
-fn render_difficulty(difficulty: Difficulty) {
-    draw_text(
-        &format!("[{}]", difficulty.name()),
-        screen_width() - 100.,
-        30.,
-        20.,
-        DARKGRAY,
-    );
-}
-
-fn render_ability_cooldowns(player: &Player) {
-    let abilities = [
-        ("DASH [SPACE]", player.dash_cd),
-        ("AREA [Q]", player.area_cd),
-        ("HEAL [E]", player.heal_cd),
-        ("RANGED [R]", player.ranged_cd),
-    ];
-
-    for (i, (name, cd)) in abilities.iter().enumerate() {
-        let y = screen_height() - 90. - (i as f32 * 18.);
-        let (text, color) = if *cd <= 0. {
-            (format!("{} READY", name), GREEN)
-        } else {
-            (format!("{} [{:.1}s]", name, cd), GRAY)
-        };
-        draw_text(&text, 20., y, 16., color);
-    }
-}
-
-fn render_level_xp(player: &Player) {
-    draw_text(
-        &format!("LVL {}", player.level),
-        230.,
-        screen_height() - 45.,
-        20.,
-        DARKBLUE,
-    );
-
-    let xp_bar_width = 80.;
-    let xp_ratio = player.xp as f32 / player.xp_to_next as f32;
-    draw_rectangle(230., screen_height() - 38., xp_bar_width, 8., DARKGRAY);
-    draw_rectangle(
-        230.,
-        screen_height() - 38.,
-        xp_bar_width * xp_ratio,
-        8.,
-        BLUE,
-    );
-}
-
-fn render_equipment_stats(player: &Player) {
-    if player.weapon_damage > 0 || player.armor > 0 {
-        draw_text(
-            &format!("DMG+{} ARM+{}", player.weapon_damage, player.armor),
-            230.,
-            screen_height() - 60.,
-            16.,
-            ORANGE,
-        );
-    }
-}
-
-fn render_poison_indicator(player: &Player) {
-    if player.poisoned > 0. {
-        draw_text(
-            &format!("POISONED {:.1}s", player.poisoned),
-            230.,
-            screen_height() - 75.,
-            16.,
-            GREEN,
-        );
-    }
-}
-
-fn render_minimap(data: &GameRenderData) {
-    let minimap_size = 100.;
-    let minimap_x = screen_width() - minimap_size - 10.;
-    let minimap_y = screen_height() - minimap_size - 10.;
-    let tile_size = minimap_size / MAP_SIZE as f32;
-
-    draw_rectangle(
-        minimap_x,
-        minimap_y,
-        minimap_size,
-        minimap_size,
-        Color::new(0., 0., 0., 0.7),
-    );
-
-    for y in 0..MAP_SIZE {
-        for x in 0..MAP_SIZE {
-            if !data.explored[y][x] {
-                continue;
-            }
-            let mx = minimap_x + x as f32 * tile_size;
-            let my = minimap_y + y as f32 * tile_size;
-
-            let color = if data.map[y][x] == Tile::Wall {
-                DARKGRAY
-            } else if data.gold.contains(&(x, y)) {
-                GOLD
-            } else if data.potions.contains(&(x, y)) {
-                RED
-            } else {
-                LIGHTGRAY
-            };
-            draw_rectangle(mx, my, tile_size, tile_size, color);
-        }
-    }
-
-    for m in data.monsters {
-        if data.explored[m.y][m.x] {
-            let mx = minimap_x + m.x as f32 * tile_size;
-            let my = minimap_y + m.y as f32 * tile_size;
-            draw_rectangle(mx, my, tile_size, tile_size, PURPLE);
-        }
-    }
-
-    let px = minimap_x + data.player.x as f32 * tile_size;
-    let py = minimap_y + data.player.y as f32 * tile_size;
-    draw_rectangle(px, py, tile_size * 1.5, tile_size * 1.5, BLUE);
-
-    draw_rectangle_lines(minimap_x, minimap_y, minimap_size, minimap_size, 2., WHITE);
-}
-
-fn render_shop(items: &[ShopItem], gold: i32) {
-    draw_rectangle(
-        0.,
-        0.,
-        screen_width(),
-        screen_height(),
-        Color::new(0., 0., 0., 0.8),
-    );
-    draw_text("SHOP", screen_width() / 2. - 50., 80., 50., GOLD);
-    draw_text(
-        &format!("Gold: {}", gold),
-        screen_width() / 2. - 60.,
-        120.,
-        24.,
-        GOLD,
-    );
-
-    for (i, item) in items.iter().enumerate() {
-        let y = 170. + (i as f32 * 50.);
-        let color = if item.purchased {
-            DARKGRAY
-        } else if gold >= item.cost {
-            WHITE
-        } else {
-            RED
-        };
-
-        let status = if item.purchased { " [SOLD]" } else { "" };
-        draw_text(
-            &format!("{}. {} - {} gold{}", i + 1, item.name, item.cost, status),
-            screen_width() / 2. - 150.,
-            y,
-            24.,
-            color,
-        );
-    }
-
-    draw_text(
-        "Press 1-4 to buy, ENTER to continue",
-        screen_width() / 2. - 160.,
-        screen_height() - 50.,
-        20.,
-        GRAY,
-    );
-}
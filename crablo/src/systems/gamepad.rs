@@ -0,0 +1,200 @@
+//! Gamepad input through gilrs (`gamepad` feature).
+//!
+//! [`Gamepads`] is polled once a frame and answers the same kind of
+//! questions as the keyboard: which buttons went down this frame and which
+//! way the sticks point. Held directions repeat like a held key would, so
+//! walking and menu navigation feel the same on either device. Without the
+//! feature every query reports nothing.
+
+/// Stick deflection that counts as a direction.
+#[cfg(feature = "gamepad")]
+const DEADZONE: f32 = 0.5;
+
+/// Delay before a held direction starts repeating, then the time between
+/// repeats, in seconds.
+const REPEAT_DELAY: f32 = 0.3;
+const REPEAT_INTERVAL: f32 = 0.12;
+
+/// Gamepad buttons the game reads, by position rather than label.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PadButton {
+    /// Bottom face button (A on Xbox pads, Cross on PlayStation).
+    South,
+    East,
+    West,
+    North,
+    Start,
+    Select,
+}
+
+impl PadButton {
+    /// Label as printed on an Xbox-style pad.
+    pub fn name(self) -> &'static str {
+        match self {
+            PadButton::South => "A",
+            PadButton::East => "B",
+            PadButton::West => "X",
+            PadButton::North => "Y",
+            PadButton::Start => "Start",
+            PadButton::Select => "Back",
+        }
+    }
+}
+
+/// A held screen direction that fires when it changes and then repeats.
+#[derive(Default)]
+struct Repeater {
+    held: (i32, i32),
+    timer: f32,
+    fired: Option<(i32, i32)>,
+}
+
+impl Repeater {
+    fn update(&mut self, held: (i32, i32), dt: f32) {
+        self.fired = None;
+        if held == (0, 0) {
+            self.held = held;
+            return;
+        }
+        if held != self.held {
+            self.timer = REPEAT_DELAY;
+            self.fired = Some(held);
+        } else {
+            self.timer -= dt;
+            if self.timer <= 0. {
+                self.timer = REPEAT_INTERVAL;
+                self.fired = Some(held);
+            }
+        }
+        self.held = held;
+    }
+}
+
+/// Every connected gamepad, read as one.
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    pressed: Vec<PadButton>,
+    /// Left stick or D-pad.
+    movement: Repeater,
+    /// Right stick.
+    aim: Repeater,
+    connected: bool,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Gamepads {
+            // No pad support on this system just means no pads
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
+            pressed: vec![],
+            movement: Repeater::default(),
+            aim: Repeater::default(),
+            connected: false,
+        }
+    }
+
+    /// Read the pads. Call once per frame before anything asks about them.
+    pub fn update(&mut self, dt: f32) {
+        self.pressed.clear();
+        let (movement, aim) = self.poll();
+        self.movement.update(movement, dt);
+        self.aim.update(aim, dt);
+    }
+
+    /// Collect button presses and return the held movement and aim
+    /// directions on screen, y pointing down.
+    #[cfg(feature = "gamepad")]
+    fn poll(&mut self) -> ((i32, i32), (i32, i32)) {
+        use gilrs::{Axis, Button, EventType};
+
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return ((0, 0), (0, 0));
+        };
+        while let Some(event) = gilrs.next_event() {
+            if let EventType::ButtonPressed(button, _) = event.event {
+                let button = match button {
+                    Button::South => PadButton::South,
+                    Button::East => PadButton::East,
+                    Button::West => PadButton::West,
+                    Button::North => PadButton::North,
+                    Button::Start => PadButton::Start,
+                    Button::Select => PadButton::Select,
+                    _ => continue,
+                };
+                self.pressed.push(button);
+            }
+        }
+
+        let stick = |x: f32, y: f32| {
+            let axis = |v: f32| {
+                if v > DEADZONE {
+                    1
+                } else if v < -DEADZONE {
+                    -1
+                } else {
+                    0
+                }
+            };
+            // gilrs has y pointing up
+            (axis(x), -axis(y))
+        };
+        let (mut movement, mut aim) = ((0, 0), (0, 0));
+        self.connected = false;
+        for (_, pad) in gilrs.gamepads() {
+            self.connected = true;
+            let dpad = (
+                pad.is_pressed(Button::DPadRight) as i32 - pad.is_pressed(Button::DPadLeft) as i32,
+                pad.is_pressed(Button::DPadDown) as i32 - pad.is_pressed(Button::DPadUp) as i32,
+            );
+            let left = stick(pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY));
+            let right = stick(pad.value(Axis::RightStickX), pad.value(Axis::RightStickY));
+            if movement == (0, 0) {
+                movement = if dpad != (0, 0) { dpad } else { left };
+            }
+            if aim == (0, 0) {
+                aim = right;
+            }
+        }
+        (movement, aim)
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    fn poll(&mut self) -> ((i32, i32), (i32, i32)) {
+        ((0, 0), (0, 0))
+    }
+
+    /// Whether any pad is plugged in.
+    pub fn connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Whether `button` went down this frame.
+    pub fn pressed(&self, button: PadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// Screen direction the left stick or D-pad stepped this frame.
+    pub fn movement(&self) -> Option<(i32, i32)> {
+        self.movement.fired
+    }
+
+    /// Screen direction the right stick stepped this frame.
+    pub fn aim(&self) -> Option<(i32, i32)> {
+        self.aim.fired
+    }
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Map step for a screen direction. The map is drawn isometrically, so
+/// screen up is diagonal on the map.
+pub fn screen_to_map(dir: (i32, i32)) -> (i32, i32) {
+    let (sx, sy) = dir;
+    ((sx + sy).signum(), (sy - sx).signum())
+}
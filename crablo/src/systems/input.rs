@@ -0,0 +1,123 @@
+//! Macroquad input adapter.
+//!
+//! Translates keyboard, mouse and gamepad state into [`PlayerCommand`]s for
+//! [`Game::step`](crate::core::Game::step), reading keys through the
+//! player's [`Bindings`]. Gamepad buttons have a fixed layout, see
+//! [`pad_button`].
+
+use macroquad::prelude::*;
+
+use crate::core::bindings::{Action, Bindings};
+use crate::core::command::PlayerCommand;
+use crate::core::Game;
+use crate::systems::gamepad::{screen_to_map, Gamepads, PadButton};
+use crate::systems::rendering::to_tile;
+
+/// Gamepad button for `action`. Movement is on the left stick and D-pad,
+/// and the shop is driven from its own screen.
+pub fn pad_button(action: Action) -> Option<PadButton> {
+    match action {
+        Action::Dash | Action::Confirm => Some(PadButton::South),
+        Action::AreaAttack => Some(PadButton::West),
+        Action::Heal => Some(PadButton::North),
+        Action::Ranged => Some(PadButton::East),
+        Action::Descend => Some(PadButton::Select),
+        Action::Pause => Some(PadButton::Start),
+        _ => None,
+    }
+}
+
+/// Whether a key bound to `action` was pressed this frame.
+pub fn key_pressed(bindings: &Bindings, action: Action) -> bool {
+    bindings
+        .keys(action)
+        .iter()
+        .flatten()
+        .any(|key| is_key_pressed(*key))
+}
+
+/// Whether a key or gamepad button for `action` was pressed this frame.
+pub fn action_pressed(bindings: &Bindings, pad: &Gamepads, action: Action) -> bool {
+    key_pressed(bindings, action) || pad_button(action).is_some_and(|button| pad.pressed(button))
+}
+
+/// Read this frame's input and turn it into commands.
+pub fn poll_commands(game: &Game, bindings: &Bindings, pad: &Gamepads) -> Vec<PlayerCommand> {
+    let mut commands = vec![];
+    let pressed = |action| action_pressed(bindings, pad, action);
+
+    if game.in_shop {
+        // Keys only, the shop screen turns pad input into purchases itself
+        let pressed = |action| key_pressed(bindings, action);
+        for (i, slot) in Action::SHOP_SLOTS.into_iter().enumerate() {
+            if pressed(slot) {
+                commands.push(PlayerCommand::ShopBuy(i));
+            }
+        }
+        if pressed(Action::Confirm) {
+            commands.push(PlayerCommand::ShopLeave);
+        }
+        return commands;
+    }
+
+    // Abilities
+    if pressed(Action::Dash) {
+        commands.push(PlayerCommand::Dash);
+    }
+    if pressed(Action::AreaAttack) {
+        commands.push(PlayerCommand::Area);
+    }
+    if pressed(Action::Heal) {
+        commands.push(PlayerCommand::Heal);
+    }
+    if pressed(Action::Ranged) {
+        commands.push(PlayerCommand::Ranged);
+    }
+    if pressed(Action::Descend) {
+        commands.push(PlayerCommand::Descend);
+    }
+
+    // Keyboard movement
+    let move_dir = if pressed(Action::MoveUp) {
+        Some((-1, -1)) // Up-left in isometric
+    } else if pressed(Action::MoveDown) {
+        Some((1, 1)) // Down-right in isometric
+    } else if pressed(Action::MoveLeft) {
+        Some((-1, 1)) // Down-left in isometric
+    } else if pressed(Action::MoveRight) {
+        Some((1, -1)) // Up-right in isometric
+    } else {
+        None
+    };
+    if let Some((dx, dy)) = move_dir.or(pad.movement().map(screen_to_map)) {
+        commands.push(PlayerCommand::Move { dx, dy });
+    }
+
+    // Right stick - attack whatever stands next to the player that way
+    if let Some((dx, dy)) = pad.aim().map(screen_to_map) {
+        let x = game.player.x as i32 + dx;
+        let y = game.player.y as i32 + dy;
+        if let Some(m) = game
+            .monsters
+            .iter()
+            .find(|m| m.x as i32 == x && m.y as i32 == y)
+        {
+            commands.push(PlayerCommand::Attack { x: m.x, y: m.y });
+        }
+    }
+
+    // Mouse - attack a monster under the cursor, otherwise walk there.
+    // Holding the button keeps attacking.
+    if is_mouse_button_down(MouseButton::Left) {
+        let (mx, my) = mouse_position();
+        if let Some((x, y)) = to_tile(mx, my, game.cam, &game.map) {
+            if game.monsters.iter().any(|m| m.x == x && m.y == y) {
+                commands.push(PlayerCommand::Attack { x, y });
+            } else {
+                commands.push(PlayerCommand::PathTo { x, y });
+            }
+        }
+    }
+
+    commands
+}
@@ -1,13 +1,17 @@
 //! Game systems module.
 //!
 //! This module contains all game systems:
-//! - [`audio`] - Sound effects and music
+//! - [`audio`] - Sound effects
+//! - [`gamepad`] - Gamepad buttons and sticks (`gamepad` feature)
 //! - [`game_renderer`] - High-level game rendering
+//! - [`input`] - Keyboard and mouse to player command adapter
 //! - [`pathfinding`] - A* and BFS pathfinding algorithms
 //! - [`rendering`] - Low-level rendering primitives
 
 pub mod audio;
 pub mod game_renderer;
+pub mod gamepad;
+pub mod input;
 pub mod pathfinding;
 pub mod rendering;
 
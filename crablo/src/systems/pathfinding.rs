@@ -1,18 +1,13 @@
 use std::collections::VecDeque;
 
-use crate::core::constants::MAP_SIZE;
-use crate::world::map::Tile;
+use crate::world::map::Map;
 
-pub fn bfs(
-    map: &[[Tile; MAP_SIZE]; MAP_SIZE],
-    start: (usize, usize),
-    goal: (usize, usize),
-) -> Vec<(usize, usize)> {
+pub fn bfs(map: &Map, start: (usize, usize), goal: (usize, usize)) -> Vec<(usize, usize)> {
     let mut q = VecDeque::from([start]);
-    let mut visited = [[false; MAP_SIZE]; MAP_SIZE];
-    visited[start.1][start.0] = true;
+    let mut visited = map.same_size(false);
+    visited.set(start.0, start.1, true);
 
-    let mut parent = [[None; MAP_SIZE]; MAP_SIZE];
+    let mut parent = map.same_size(None);
 
     while let Some(curr) = q.pop_front() {
         if curr == goal {
@@ -21,7 +16,7 @@ pub fn bfs(
             while c != start {
                 path.push(c);
 
-                c = parent[c.1][c.0].unwrap()
+                c = parent.get(c.0, c.1).flatten().unwrap()
             }
 
             path.reverse();
@@ -29,47 +24,52 @@ pub fn bfs(
             return path;
         }
 
-        // check neighbors - 8 directions (including diagonals)
-        for (dx, dy) in [
-            (0, -1),
-            (0, 1),
-            (-1, 0),
-            (1, 0), // cardinal
-            (-1, -1),
-            (-1, 1),
-            (1, -1),
-            (1, 1), // diagonal
-        ] {
-            let nx = curr.0 as i32 + dx;
-            let ny = curr.1 as i32 + dy;
+        for (nx, ny) in steps(map, curr) {
+            if visited.get(nx, ny) == Some(false) {
+                visited.set(nx, ny, true);
+                parent.set(nx, ny, Some(curr));
+                q.push_back((nx, ny));
+            }
+        }
+    }
+
+    vec![]
+}
 
-            // bounds check before casting to usize
-            if nx >= 0 && ny >= 0 {
-                let (nx, ny) = (nx as usize, ny as usize);
-                if nx < MAP_SIZE && ny < MAP_SIZE && !visited[ny][nx] && map[ny][nx] == Tile::Floor
-                {
-                    // For diagonal movement, check that we're not cutting through walls
-                    let is_diagonal = dx != 0 && dy != 0;
-                    if is_diagonal {
-                        let cx = curr.0;
-                        let cy = curr.1;
-                        // Check both adjacent cardinal tiles are walkable
-                        let adj1 = map[cy][(cx as i32 + dx) as usize];
-                        let adj2 = map[(cy as i32 + dy) as usize][cx];
-                        if adj1 != Tile::Floor || adj2 != Tile::Floor {
-                            continue; // Can't cut through wall corners
-                        }
-                    }
+/// Walking distance in steps from `start` to every tile, `None` where it
+/// can't be reached.
+pub fn distances(map: &Map, start: (usize, usize)) -> Map<Option<u32>> {
+    let mut distance = map.same_size(None);
+    distance.set(start.0, start.1, Some(0));
+    let mut q = VecDeque::from([start]);
 
-                    visited[ny][nx] = true;
-                    parent[ny][nx] = Some(curr);
-                    q.push_back((nx, ny));
-                }
+    while let Some(curr) = q.pop_front() {
+        let d = distance.get(curr.0, curr.1).flatten().unwrap_or(0);
+        for (nx, ny) in steps(map, curr) {
+            if distance.get(nx, ny) == Some(None) {
+                distance.set(nx, ny, Some(d + 1));
+                q.push_back((nx, ny));
             }
         }
     }
+    distance
+}
 
-    vec![]
+/// Walkable tiles one step from `pos` - 8 directions (including diagonals)
+fn steps(map: &Map, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    map.neighbours(pos).filter_map(move |((nx, ny), (dx, dy))| {
+        if !map.is_floor(nx, ny) {
+            return None;
+        }
+        // For diagonal movement, check that we're not cutting through walls
+        let is_diagonal = dx != 0 && dy != 0;
+        let (cx, cy) = pos;
+        // Check both adjacent cardinal tiles are walkable
+        if is_diagonal && (!map.is_floor(nx, cy) || !map.is_floor(cx, ny)) {
+            return None; // Can't cut through wall corners
+        }
+        Some((nx, ny))
+    })
 }
 
 pub fn dist(p1: (usize, usize), p2: (usize, usize)) -> i32 {
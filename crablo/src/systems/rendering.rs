@@ -1,6 +1,7 @@
 use macroquad::prelude::*;
 
-use crate::core::constants::{MAP_SIZE, TILE_HEIGHT, TILE_WIDTH};
+use crate::core::constants::{TILE_HEIGHT, TILE_WIDTH};
+use crate::world::map::Map;
 
 pub fn to_screen(x: usize, y: usize, cam: (f32, f32)) -> (f32, f32) {
     (
@@ -9,55 +10,31 @@ pub fn to_screen(x: usize, y: usize, cam: (f32, f32)) -> (f32, f32) {
     )
 }
 
-pub fn to_tile(sx: f32, sy: f32, cam: (f32, f32)) -> Option<(usize, usize)> {
+/// The tile of `map` under screen position `(sx, sy)`, if any.
+pub fn to_tile(sx: f32, sy: f32, cam: (f32, f32), map: &Map) -> Option<(usize, usize)> {
     let (ax, ay) = (sx - cam.0, sy - cam.1);
 
     let tx = (ax / TILE_WIDTH + ay / TILE_HEIGHT) / 2.;
     let ty = (ay / TILE_HEIGHT - ax / TILE_WIDTH) / 2.;
 
-    if tx >= 0. && ty >= 0. && (tx as usize) < MAP_SIZE && (ty as usize) < MAP_SIZE {
-        Some((tx as usize, ty as usize))
-    } else {
-        None
-    }
-}
-
-use crate::world::entities::MonsterType;
-
-pub fn draw_stickman(x: usize, y: usize, cam: (f32, f32), enemy: bool) {
-    draw_stickman_typed(x, y, cam, enemy, None);
+    // Floor rather than cast, which would round -0.5 onto the map
+    let (tx, ty) = (tx.floor() as i32, ty.floor() as i32);
+    map.in_bounds(tx, ty).then_some((tx as usize, ty as usize))
 }
 
-pub fn draw_stickman_typed(
+/// Draw a stickman with a custom colour and size, e.g. for monster archetypes.
+pub fn draw_stickman_styled(
     x: usize,
     y: usize,
     cam: (f32, f32),
     enemy: bool,
-    monster_type: Option<MonsterType>,
+    color: Color,
+    scale: f32,
 ) {
     let (sx, mut sy) = to_screen(x, y, cam);
 
     sy += 16.;
 
-    // Determine color based on monster type
-    let color = if !enemy {
-        BLACK
-    } else {
-        match monster_type {
-            Some(MonsterType::Fast) => BLUE,
-            Some(MonsterType::Tank) => DARKPURPLE,
-            Some(MonsterType::Boss) => MAROON,
-            _ => BLACK,
-        }
-    };
-
-    // Scale for tank and boss monsters
-    let scale = match monster_type {
-        Some(MonsterType::Tank) => 1.3,
-        Some(MonsterType::Boss) => 1.8,
-        _ => 1.0,
-    };
-
     // shadow
     draw_ellipse(
         sx,
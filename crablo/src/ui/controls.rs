@@ -0,0 +1,156 @@
+use macroquad::prelude::*;
+
+use super::dialog::{draw_prompt, ConfirmScene};
+use super::layout::Anchor;
+use super::{draw_dim, App, Scene, Transition};
+use crate::core::bindings::{is_bindable, key_name, Action, Bindings, KEYS_PER_ACTION};
+
+/// Every action with its keys. Rebinding a key that another action uses
+/// asks before moving it over.
+#[derive(Default)]
+pub struct ControlsScene {
+    selected: usize,
+    slot: usize,
+}
+
+impl Scene for ControlsScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let action = Action::ALL[self.selected];
+        if app.back_pressed() {
+            app.save_settings();
+            return Transition::Pop;
+        }
+        // Only the arrow keys move around here, so any other key can be
+        // selected and rebound without getting in the way
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len();
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % Action::ALL.len();
+        }
+        if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::Right) {
+            self.slot = (self.slot + 1) % KEYS_PER_ACTION;
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            return Transition::Push(Box::new(RebindScene {
+                action,
+                slot: self.slot,
+            }));
+        }
+        if is_key_pressed(KeyCode::Backspace) || is_key_pressed(KeyCode::Delete) {
+            let others = app.settings.bindings.keys(action);
+            if others.iter().flatten().count() > 1 {
+                let mut settings = app.settings.clone();
+                settings.bindings.set(action, self.slot, None);
+                app.change_settings(settings);
+            } else {
+                app.notify(format!("{} needs at least one key", action.name()));
+            }
+        }
+        if is_key_pressed(KeyCode::F5) {
+            return Transition::Push(Box::new(ConfirmScene::new(
+                "Reset all controls?".to_string(),
+                |app| {
+                    let mut settings = app.settings.clone();
+                    settings.bindings = Bindings::default();
+                    app.change_settings(settings);
+                    Transition::Pop
+                },
+            )));
+        }
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        draw_dim(Color::new(0.1, 0.1, 0.2, 1.));
+        ui.text("CONTROLS", Anchor::Top, vec2(0., 20.), 44., GOLD);
+
+        let label_x = ui.place(Anchor::Top, Vec2::ZERO, vec2(-260., 0.)).x;
+        let key_x = [
+            ui.place(Anchor::Top, Vec2::ZERO, vec2(0., 0.)).x,
+            ui.place(Anchor::Top, Vec2::ZERO, vec2(150., 0.)).x,
+        ];
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let y = ui.y(Anchor::Top, 75. + i as f32 * 29.);
+            let selected = i == self.selected;
+            ui.text_at(
+                action.name(),
+                vec2(label_x, y),
+                22.,
+                if selected { YELLOW } else { WHITE },
+            );
+            for (slot, key) in app.settings.bindings.keys(*action).iter().enumerate() {
+                let name = key.map_or("-".to_string(), key_name);
+                let (text, color) = if selected && slot == self.slot {
+                    (format!("[{}]", name), YELLOW)
+                } else {
+                    (name, LIGHTGRAY)
+                };
+                ui.text_at(&text, vec2(key_x[slot], y), 22., color);
+            }
+        }
+
+        ui.text(
+            "ARROWS select, ENTER rebind, DEL clear, F5 reset, ESC back",
+            Anchor::Bottom,
+            Vec2::ZERO,
+            20.,
+            GRAY,
+        );
+    }
+}
+
+/// Waiting for the key to bind to one slot of an action.
+struct RebindScene {
+    action: Action,
+    slot: usize,
+}
+
+impl Scene for RebindScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let Some(key) = get_last_key_pressed() else {
+            return Transition::None;
+        };
+        if key == KeyCode::Escape {
+            return Transition::Pop;
+        }
+        if !is_bindable(key) {
+            return Transition::None;
+        }
+
+        let (action, slot) = (self.action, self.slot);
+        let mut settings = app.settings.clone();
+        if let Err(other) = settings.bindings.swap(action, slot, key) {
+            app.notify(format!("{} needs at least one key", other.name()));
+            return Transition::Pop;
+        }
+        let rebind = move |app: &mut App| {
+            app.change_settings(settings);
+            Transition::Pop
+        };
+
+        match app.settings.bindings.action_for(key) {
+            Some((other, _)) if other != action => {
+                Transition::Replace(Box::new(ConfirmScene::new(
+                    format!("{} is used by {}. Swap?", key_name(key), other.name()),
+                    rebind,
+                )))
+            }
+            _ => rebind(app),
+        }
+    }
+
+    fn draw(&self, app: &App) {
+        draw_prompt(
+            &app.layout(),
+            &format!("Press a key for {}", self.action.name()),
+            "ESC: Cancel",
+        );
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,67 @@
+use macroquad::prelude::*;
+
+use super::layout::{Anchor, Layout};
+use super::{draw_dim, fill, App, Scene, Transition};
+use crate::systems::gamepad::PadButton;
+
+type OnConfirm = Box<dyn FnOnce(&mut App) -> Transition>;
+
+/// Yes/no question over the current screen. Y (or A on a gamepad) runs the
+/// action and applies the transition it returns; N, ESC or B just closes the
+/// dialog.
+pub struct ConfirmScene {
+    question: String,
+    on_confirm: Option<OnConfirm>,
+}
+
+impl ConfirmScene {
+    pub fn new(
+        question: String,
+        on_confirm: impl FnOnce(&mut App) -> Transition + 'static,
+    ) -> Self {
+        ConfirmScene {
+            question,
+            on_confirm: Some(Box::new(on_confirm)),
+        }
+    }
+}
+
+impl Scene for ConfirmScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if is_key_pressed(KeyCode::Y) || app.pad.pressed(PadButton::South) {
+            return match self.on_confirm.take() {
+                Some(on_confirm) => on_confirm(app),
+                None => Transition::Pop,
+            };
+        }
+        if is_key_pressed(KeyCode::N) || app.back_pressed() {
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let hint = if app.pad.connected() {
+            "A: Yes  B: No"
+        } else {
+            "Y: Yes  N: No"
+        };
+        draw_prompt(&app.layout(), &self.question, hint);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+/// A box in the middle of the screen with a line of text and a key hint.
+pub(super) fn draw_prompt(ui: &Layout, text: &str, hint: &str) {
+    draw_dim(Color::new(0., 0., 0., 0.5));
+    let width = ui.measure(text, 28.).width.max(ui.px(300.)) + ui.px(60.);
+    let panel = ui.rect(Anchor::Center, vec2(width / ui.scale(), 110.), Vec2::ZERO);
+    fill(panel, Color::new(0.15, 0.15, 0.25, 1.));
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, ui.px(2.), GOLD);
+    let left = panel.x + ui.px(30.);
+    ui.text_at(text, vec2(left, panel.y + ui.px(25.)), 28., WHITE);
+    ui.text_at(hint, vec2(left, panel.y + ui.px(70.)), 20., GRAY);
+}
@@ -0,0 +1,161 @@
+use macroquad::prelude::*;
+
+use super::hall_of_fame::HallOfFameScene;
+use super::hud::draw_hud;
+use super::keyboard::{KeyboardInput, OnScreenKeyboard, NAME_CHARS};
+use super::layout::Anchor;
+use super::{draw_dim, draw_text_entry, format_duration, App, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::systems::gamepad::PadButton;
+
+/// End of a run, won or lost, over the final state of the dungeon.
+pub struct GameOverScene;
+
+impl Scene for GameOverScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if app.menu_pressed(Action::Confirm) {
+            return Transition::Replace(Box::new(EnterNameScene::default()));
+        }
+        if is_key_pressed(KeyCode::H) || app.pad.pressed(PadButton::North) {
+            return Transition::Reset(Box::new(HallOfFameScene::new(app)));
+        }
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        let game = &app.game;
+        game.draw(app.render_style());
+        draw_hud(&ui, app, game);
+        draw_dim(Color::new(1., 1., 1., 0.7));
+
+        let (msg, col) = if game.won {
+            ("VICTORY", GOLD)
+        } else {
+            ("GAME OVER", RED)
+        };
+        ui.text(msg, Anchor::Center, vec2(0., -40.), 60., col);
+        ui.text(
+            &format!("Final Score: {}", game.score),
+            Anchor::Center,
+            vec2(0., 15.),
+            30.,
+            BLACK,
+        );
+
+        let summary = [
+            format!("Floor {}  -  Level {}", game.floor, game.player.level),
+            format!(
+                "Kills: {}  Gold: {}  Damage: {}",
+                game.total_kills, game.player.gold, game.total_damage_dealt
+            ),
+            format!("Time: {}", format_duration(game.playtime() as i32)),
+        ];
+        for (i, line) in summary.iter().enumerate() {
+            ui.text(
+                line,
+                Anchor::Center,
+                vec2(0., 50. + i as f32 * 22.),
+                20.,
+                DARKGRAY,
+            );
+        }
+
+        if let Some(cause) = game.death_cause.as_ref().filter(|_| !game.won) {
+            ui.text(
+                &format!("Killed by {}", cause.name()),
+                Anchor::Center,
+                vec2(0., 118.),
+                18.,
+                DARKGRAY,
+            );
+        }
+
+        ui.text(
+            &format!("Press {} to save score", app.hint(Action::Confirm)),
+            Anchor::Center,
+            vec2(0., 148.),
+            20.,
+            GRAY,
+        );
+        ui.text(
+            &format!(
+                "Press {} for Hall of Fame",
+                if app.pad.connected() { "Y" } else { "H" }
+            ),
+            Anchor::Center,
+            vec2(0., 173.),
+            20.,
+            GRAY,
+        );
+    }
+}
+
+/// Typing a name for the Hall of Fame.
+pub struct EnterNameScene {
+    name: String,
+    keyboard: OnScreenKeyboard,
+}
+
+impl Default for EnterNameScene {
+    fn default() -> Self {
+        EnterNameScene {
+            name: String::new(),
+            keyboard: OnScreenKeyboard::new(NAME_CHARS),
+        }
+    }
+}
+
+impl Scene for EnterNameScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let mut typed = get_char_pressed();
+        let mut done = is_key_pressed(KeyCode::Enter);
+        match self.keyboard.update(&app.pad) {
+            Some(KeyboardInput::Char(c)) => typed = Some(c),
+            Some(KeyboardInput::Backspace) => {
+                self.name.pop();
+            }
+            Some(KeyboardInput::Done) => done = true,
+            None => {}
+        }
+        if let Some(c) = typed {
+            if c.is_alphanumeric() && self.name.len() < 12 {
+                self.name.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.name.pop();
+        }
+
+        if done && !self.name.is_empty() {
+            let game = &app.game;
+            let replay = game.replay.to_json().unwrap_or_default();
+            if let Err(e) = app.storage.save_score(
+                &self.name,
+                game.score,
+                game.seed,
+                &replay,
+                &game.run_stats(),
+            ) {
+                app.notify(format!("Couldn't save score: {}", e));
+            }
+            return Transition::Reset(Box::new(HallOfFameScene::new(app)));
+        }
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        if app.pad.connected() {
+            draw_text_entry(&ui, "Enter your name:", &self.name, "Start to confirm");
+            self.keyboard.draw(&ui, 110.);
+        } else {
+            draw_text_entry(
+                &ui,
+                "Enter your name:",
+                &self.name,
+                "Press ENTER to confirm",
+            );
+        }
+    }
+}
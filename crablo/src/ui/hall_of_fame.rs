@@ -0,0 +1,250 @@
+use macroquad::prelude::*;
+
+use super::dialog::ConfirmScene;
+use super::layout::Anchor;
+use super::replay::ReplayScene;
+use super::{draw_dim, format_duration, App, MenuScene, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::core::constants::Difficulty;
+use crate::core::replay::Replay;
+use crate::core::rng::format_seed;
+use crate::core::storage::ScoreEntry;
+use crate::systems::gamepad::PadButton;
+
+/// Hall of Fame difficulty filters, in the order LEFT/RIGHT cycles them.
+const SCORE_FILTERS: [Option<Difficulty>; 4] = [
+    None,
+    Some(Difficulty::Easy),
+    Some(Difficulty::Normal),
+    Some(Difficulty::Hard),
+];
+
+/// Top scores, filterable by difficulty.
+pub struct HallOfFameScene {
+    filter: usize,
+    selected: usize,
+    scores: Vec<ScoreEntry>,
+}
+
+impl HallOfFameScene {
+    pub fn new(app: &App) -> Self {
+        let mut scene = HallOfFameScene {
+            filter: 0,
+            selected: 0,
+            scores: vec![],
+        };
+        scene.refresh(app);
+        scene
+    }
+
+    fn refresh(&mut self, app: &App) {
+        self.scores = app
+            .storage
+            .get_top_scores(10, SCORE_FILTERS[self.filter])
+            .unwrap_or_default();
+        self.selected = self.selected.min(self.scores.len().saturating_sub(1));
+    }
+}
+
+impl Scene for HallOfFameScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if app.back_pressed() {
+            return Transition::Reset(Box::new(MenuScene::default()));
+        }
+        if is_key_pressed(KeyCode::R) || app.pad.pressed(PadButton::North) {
+            return Transition::Push(Box::new(ConfirmScene::new(
+                "Reset all scores?".to_string(),
+                |app| {
+                    if let Err(e) = app.storage.reset_scores() {
+                        app.notify(format!("Couldn't reset scores: {}", e));
+                    }
+                    Transition::Pop
+                },
+            )));
+        }
+        // Arrows rather than the movement keys, which W and D would clash with
+        if is_key_pressed(KeyCode::Up) || app.pad_moved((0, -1)) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Down) || app.pad_moved((0, 1)) {
+            self.selected += 1;
+        }
+        if is_key_pressed(KeyCode::Left) || app.pad_moved((-1, 0)) {
+            self.filter = (self.filter + SCORE_FILTERS.len() - 1) % SCORE_FILTERS.len();
+            self.selected = 0;
+            self.refresh(app);
+        }
+        if is_key_pressed(KeyCode::Right) || app.pad_moved((1, 0)) {
+            self.filter = (self.filter + 1) % SCORE_FILTERS.len();
+            self.selected = 0;
+            self.refresh(app);
+        }
+        if is_key_pressed(KeyCode::D) || app.menu_pressed(Action::Confirm) {
+            if let Some(entry) = self.scores.get(self.selected).cloned() {
+                return Transition::Push(Box::new(ScoreDetailScene { entry }));
+            }
+        }
+        if is_key_pressed(KeyCode::W) || app.pad.pressed(PadButton::West) {
+            let replay = self
+                .scores
+                .get(self.selected)
+                .and_then(|entry| app.storage.load_replay(entry.id).ok().flatten())
+                .and_then(|json| Replay::from_json(&json).ok());
+            match replay {
+                Some(replay) if !replay.matches_roster() => {
+                    app.notify("Replay was recorded with different monsters".to_string());
+                }
+                Some(replay) => return Transition::Push(Box::new(ReplayScene::new(replay))),
+                None => {}
+            }
+        }
+        Transition::None
+    }
+
+    fn resume(&mut self, app: &mut App) {
+        self.refresh(app);
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        draw_dim(Color::new(0.1, 0.1, 0.2, 1.));
+
+        ui.text("HALL OF FAME", Anchor::Top, vec2(0., 40.), 50., GOLD);
+
+        let filter = SCORE_FILTERS[self.filter];
+        ui.text(
+            &format!("< {} >", filter.map_or("All", |d| d.name())),
+            Anchor::Top,
+            vec2(0., 92.),
+            22.,
+            LIGHTGRAY,
+        );
+
+        let rows: Vec<String> = self
+            .scores
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let prefix = if i == self.selected { "> " } else { "  " };
+                format!(
+                    "{}{}. {} - {}  {}  {}{}",
+                    prefix,
+                    entry.rank,
+                    entry.name,
+                    entry.score,
+                    entry.seed.map(format_seed).unwrap_or_default(),
+                    entry.stats.difficulty.map_or("", |d| d.name()),
+                    if entry.stats.won { "  WIN" } else { "" }
+                )
+            })
+            .collect();
+        let x = ui.block_x(rows.iter().map(|r| r.as_str()), 28., Anchor::Top, 0.);
+        for (i, row) in rows.iter().enumerate() {
+            let color = match i {
+                0 => GOLD,
+                1 => LIGHTGRAY,
+                2 => ORANGE,
+                _ => WHITE,
+            };
+            let y = ui.y(Anchor::Top, 120. + i as f32 * 35.);
+            ui.text_at(row, vec2(x, y), 28., color);
+        }
+
+        let pad = app.pad.connected();
+        let (details, watch, back, reset) = if pad {
+            ("A", "X", "B", "Y")
+        } else {
+            ("D", "W", "ESC", "R")
+        };
+        let mut hint = format!("UP/DOWN select, LEFT/RIGHT filter, {} details", details);
+        if self.scores.get(self.selected).is_some_and(|e| e.has_replay) {
+            hint += &format!(", {} to WATCH", watch);
+        }
+        ui.text(&hint, Anchor::Bottom, vec2(0., -60.), 20., GRAY);
+        ui.text(
+            &format!("Press {} to return to menu", back),
+            Anchor::Bottom,
+            vec2(0., -30.),
+            20.,
+            GRAY,
+        );
+        ui.text(
+            &format!("Press {} to RESET all scores", reset),
+            Anchor::Bottom,
+            Vec2::ZERO,
+            20.,
+            RED,
+        );
+    }
+}
+
+/// Everything recorded about one score.
+pub struct ScoreDetailScene {
+    entry: ScoreEntry,
+}
+
+impl Scene for ScoreDetailScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if app.back_pressed() || app.menu_pressed(Action::Confirm) {
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        draw_dim(Color::new(0.1, 0.1, 0.2, 1.));
+
+        let entry = &self.entry;
+        let stats = &entry.stats;
+        ui.text(
+            &format!("#{} {}", entry.rank, entry.name),
+            Anchor::Top,
+            vec2(0., 40.),
+            40.,
+            GOLD,
+        );
+
+        let outcome = match &stats.cause {
+            _ if stats.won => "Victory".to_string(),
+            Some(cause) => format!("Killed by {}", cause),
+            None => "-".to_string(),
+        };
+        let lines = [
+            ("Score", entry.score.to_string()),
+            ("Outcome", outcome),
+            (
+                "Difficulty",
+                stats.difficulty.map_or("-", |d| d.name()).to_string(),
+            ),
+            ("Floor", stats.floor.to_string()),
+            ("Level", stats.level.to_string()),
+            ("Kills", stats.kills.to_string()),
+            ("Gold", stats.gold.to_string()),
+            ("Damage dealt", stats.damage_dealt.to_string()),
+            ("Duration", format_duration(stats.duration)),
+            ("Seed", entry.seed.map(format_seed).unwrap_or_default()),
+            ("Date", entry.created_at.clone()),
+        ];
+        // Two columns either side of the middle
+        let label_x = ui.place(Anchor::Top, Vec2::ZERO, vec2(-150., 0.)).x;
+        let value_x = ui.place(Anchor::Top, Vec2::ZERO, vec2(20., 0.)).x;
+        for (i, (label, value)) in lines.iter().enumerate() {
+            let y = ui.y(Anchor::Top, 115. + i as f32 * 32.);
+            ui.text_at(label, vec2(label_x, y), 24., LIGHTGRAY);
+            ui.text_at(value, vec2(value_x, y), 24., WHITE);
+        }
+
+        ui.text(
+            if app.pad.connected() {
+                "Press B to go back"
+            } else {
+                "Press ESC to go back"
+            },
+            Anchor::Bottom,
+            Vec2::ZERO,
+            20.,
+            GRAY,
+        );
+    }
+}
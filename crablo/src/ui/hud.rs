@@ -1,199 +1,222 @@
-use crate::core::constants::{Difficulty, MAP_SIZE};
-use crate::world::map::Tile;
 use macroquad::prelude::*;
 
-pub struct HudData<'a> {
-    pub hp: i32,
-    pub max_hp: i32,
-    pub score: i32,
-    pub floor: i32,
-    pub level: i32,
-    pub xp: i32,
-    pub xp_to_next: i32,
-    pub weapon_damage: i32,
-    pub armor: i32,
-    pub poisoned: f32,
-    pub dash_cd: f32,
-    pub area_cd: f32,
-    pub heal_cd: f32,
-    pub ranged_cd: f32,
-    pub difficulty: Difficulty,
-    // Minimap data
-    pub player_pos: (usize, usize),
-    pub map: &'a [[Tile; MAP_SIZE]; MAP_SIZE],
-    pub explored: &'a [[bool; MAP_SIZE]; MAP_SIZE],
-    pub gold: &'a [(usize, usize)],
-    pub potions: &'a [(usize, usize)],
-    pub monster_positions: &'a [(usize, usize)],
+use super::fill;
+use super::layout::{Anchor, Layout};
+use super::App;
+use crate::core::bindings::Action;
+use crate::core::player::Player;
+use crate::core::rng::format_seed;
+use crate::core::settings::Colors;
+use crate::core::Game;
+use crate::world::generator::Biome;
+use crate::world::map::Tile;
+
+/// Draw the in-game HUD and minimap over the current run.
+pub fn draw_hud(ui: &Layout, app: &App, game: &Game) {
+    let colors = &app.settings.palette.colors();
+    draw_hp_bar(ui, &game.player, colors);
+    draw_score_floor(ui, game.score, game.player.gold, game.floor);
+    draw_difficulty(ui, game);
+    draw_ability_cooldowns(ui, app, &game.player);
+    draw_level_xp(ui, &game.player);
+    draw_equipment_stats(ui, &game.player);
+    draw_poison_indicator(ui, &game.player);
+    draw_stairs_hint(ui, app, game);
+    draw_minimap(ui, game, colors);
 }
 
-pub fn draw_hud(data: &HudData) {
-    // HP Bar
-    let hp_bar_width = 200.;
-    let hp_bar_height = 20.;
-    let hp_ratio = data.hp as f32 / data.max_hp as f32;
-    draw_rectangle(
-        20.,
-        screen_height() - 50.,
-        hp_bar_width,
-        hp_bar_height,
-        DARKGRAY,
-    );
-    draw_rectangle(
-        20.,
-        screen_height() - 50.,
-        hp_bar_width * hp_ratio,
-        hp_bar_height,
-        RED,
-    );
-    draw_rectangle_lines(
-        20.,
-        screen_height() - 50.,
-        hp_bar_width,
-        hp_bar_height,
-        2.,
-        BLACK,
+fn draw_hp_bar(ui: &Layout, player: &Player, colors: &Colors) {
+    let bar = ui.rect(Anchor::BottomLeft, vec2(200., 20.), vec2(10., -20.));
+    let hp_ratio = (player.hp as f32 / player.max_hp as f32).clamp(0., 1.);
+
+    fill(bar, DARKGRAY);
+    fill(
+        Rect::new(bar.x, bar.y, bar.w * hp_ratio, bar.h),
+        colors.player_health,
     );
-    draw_text(
-        &format!("{}/{}", data.hp, data.max_hp),
-        25.,
-        screen_height() - 35.,
+    draw_rectangle_lines(bar.x, bar.y, bar.w, bar.h, ui.px(2.), BLACK);
+
+    let text = format!("{}/{}", player.hp, player.max_hp);
+    let dims = ui.measure(&text, 18.);
+    ui.text_at(
+        &text,
+        vec2(bar.x + ui.px(5.), bar.y + (bar.h - dims.height) / 2.),
         18.,
         WHITE,
     );
+}
 
-    // Score and Floor
-    draw_text(
-        &format!("SCORE: {}  FLOOR: {}", data.score, data.floor),
-        20.,
-        screen_height() - 70.,
+fn draw_score_floor(ui: &Layout, score: i32, gold: i32, floor: i32) {
+    ui.text(
+        &format!("SCORE: {}  GOLD: {}  FLOOR: {}", score, gold, floor),
+        Anchor::BottomLeft,
+        vec2(10., -55.),
         24.,
         BLACK,
     );
+}
 
-    // Difficulty indicator
-    draw_text(
-        &format!("[{}]", data.difficulty.name()),
-        screen_width() - 100.,
-        30.,
+fn draw_difficulty(ui: &Layout, game: &Game) {
+    ui.text(
+        &format!("[{}]", game.difficulty.name()),
+        Anchor::TopRight,
+        vec2(-10., 5.),
         20.,
         DARKGRAY,
     );
+    ui.text(
+        &format!("SEED {}", format_seed(game.seed)),
+        Anchor::TopRight,
+        vec2(-10., 27.),
+        18.,
+        GRAY,
+    );
+    ui.text(
+        Biome::for_floor(game.floor).name(),
+        Anchor::TopRight,
+        vec2(-10., 47.),
+        18.,
+        GRAY,
+    );
+}
 
-    // Ability cooldowns
+/// Ability timers, each with the key or gamepad button that fires it.
+fn draw_ability_cooldowns(ui: &Layout, app: &App, player: &Player) {
     let abilities = [
-        ("DASH [SPACE]", data.dash_cd),
-        ("AREA [Q]", data.area_cd),
-        ("HEAL [E]", data.heal_cd),
-        ("RANGED [R]", data.ranged_cd),
+        ("DASH", Action::Dash, player.dash_cd),
+        ("AREA", Action::AreaAttack, player.area_cd),
+        ("HEAL", Action::Heal, player.heal_cd),
+        ("RANGED", Action::Ranged, player.ranged_cd),
     ];
-    for (i, (name, cd)) in abilities.iter().enumerate() {
-        let y = screen_height() - 90. - (i as f32 * 18.);
-        let text = if *cd <= 0. {
-            format!("{} READY", name)
+
+    for (i, (name, action, cd)) in abilities.iter().enumerate() {
+        let name = format!("{} [{}]", name, app.hint(*action).to_uppercase());
+        let (text, color) = if *cd <= 0. {
+            (format!("{} READY", name), GREEN)
         } else {
-            format!("{} [{:.1}s]", name, cd)
+            (format!("{} [{:.1}s]", name, cd), GRAY)
         };
-        let color = if *cd <= 0. { GREEN } else { GRAY };
-        draw_text(&text, 20., y, 16., color);
+        ui.text(
+            &text,
+            Anchor::BottomLeft,
+            vec2(10., -80. - i as f32 * 18.),
+            16.,
+            color,
+        );
     }
+}
 
-    // Level and XP bar
-    draw_text(
-        &format!("LVL {}", data.level),
-        230.,
-        screen_height() - 45.,
+fn draw_level_xp(ui: &Layout, player: &Player) {
+    ui.text(
+        &format!("LVL {}", player.level),
+        Anchor::BottomLeft,
+        vec2(220., -32.),
         20.,
         DARKBLUE,
     );
-    let xp_bar_width = 80.;
-    let xp_ratio = data.xp as f32 / data.xp_to_next as f32;
-    draw_rectangle(230., screen_height() - 38., xp_bar_width, 8., DARKGRAY);
-    draw_rectangle(
-        230.,
-        screen_height() - 38.,
-        xp_bar_width * xp_ratio,
-        8.,
-        BLUE,
-    );
 
-    // Equipment stats
-    if data.weapon_damage > 0 || data.armor > 0 {
-        draw_text(
-            &format!("DMG+{} ARM+{}", data.weapon_damage, data.armor),
-            230.,
-            screen_height() - 60.,
+    let bar = ui.rect(Anchor::BottomLeft, vec2(80., 8.), vec2(220., -20.));
+    let xp_ratio = (player.xp as f32 / player.xp_to_next as f32).clamp(0., 1.);
+    fill(bar, DARKGRAY);
+    fill(Rect::new(bar.x, bar.y, bar.w * xp_ratio, bar.h), BLUE);
+}
+
+fn draw_equipment_stats(ui: &Layout, player: &Player) {
+    if player.weapon_damage > 0 || player.armor > 0 {
+        ui.text(
+            &format!("DMG+{} ARM+{}", player.weapon_damage, player.armor),
+            Anchor::BottomLeft,
+            vec2(220., -52.),
             16.,
             ORANGE,
         );
     }
+}
 
-    // Poison indicator
-    if data.poisoned > 0. {
-        draw_text(
-            &format!("POISONED {:.1}s", data.poisoned),
-            230.,
-            screen_height() - 75.,
+fn draw_poison_indicator(ui: &Layout, player: &Player) {
+    if player.poisoned > 0. {
+        ui.text(
+            &format!("POISONED {:.1}s", player.poisoned),
+            Anchor::BottomLeft,
+            vec2(220., -70.),
             16.,
             GREEN,
         );
     }
+}
 
-    // Minimap
-    draw_minimap(data);
+/// What standing on the stairs does, or why it doesn't.
+fn draw_stairs_hint(ui: &Layout, app: &App, game: &Game) {
+    if !game.on_stairs() {
+        return;
+    }
+    let (text, color) = if game.stairs_sealed() {
+        ("SEALED UNTIL THE BOSS FALLS".to_string(), RED)
+    } else if game.floor >= game.difficulty.final_floor() {
+        (
+            format!(
+                "[{}] LEAVE THE DUNGEON",
+                app.hint(Action::Descend).to_uppercase()
+            ),
+            GOLD,
+        )
+    } else {
+        (
+            format!("[{}] DESCEND", app.hint(Action::Descend).to_uppercase()),
+            WHITE,
+        )
+    };
+    ui.text(&text, Anchor::Bottom, vec2(0., -40.), 22., color);
 }
 
-fn draw_minimap(data: &HudData) {
-    let minimap_size = 100.;
-    let minimap_x = screen_width() - minimap_size - 10.;
-    let minimap_y = screen_height() - minimap_size - 10.;
-    let tile_size = minimap_size / MAP_SIZE as f32;
-
-    // Background
-    draw_rectangle(
-        minimap_x,
-        minimap_y,
-        minimap_size,
-        minimap_size,
-        Color::new(0., 0., 0., 0.7),
+fn draw_minimap(ui: &Layout, game: &Game, colors: &Colors) {
+    // The longer side of the floor always spans 100 reference pixels
+    let (width, height) = (game.map.width() as f32, game.map.height() as f32);
+    let tile_size = 100. / width.max(height);
+    let area = ui.rect(
+        Anchor::BottomRight,
+        vec2(width * tile_size, height * tile_size),
+        Vec2::ZERO,
     );
+    let tile_size = ui.px(tile_size);
+    let tile = |x: usize, y: usize, size: f32, color: Color| {
+        draw_rectangle(
+            area.x + x as f32 * tile_size,
+            area.y + y as f32 * tile_size,
+            size,
+            size,
+            color,
+        );
+    };
+
+    fill(area, Color::new(0., 0., 0., 0.7));
 
-    // Tiles
-    for y in 0..MAP_SIZE {
-        for x in 0..MAP_SIZE {
-            if !data.explored[y][x] {
-                continue;
-            }
-            let mx = minimap_x + x as f32 * tile_size;
-            let my = minimap_y + y as f32 * tile_size;
-
-            let color = if data.map[y][x] == Tile::Wall {
-                DARKGRAY
-            } else if data.gold.contains(&(x, y)) {
-                GOLD
-            } else if data.potions.contains(&(x, y)) {
-                RED
-            } else {
-                LIGHTGRAY
-            };
-            draw_rectangle(mx, my, tile_size, tile_size, color);
+    let explored = |x, y| game.explored.get(x, y) == Some(true);
+    for (x, y) in game.map.positions() {
+        if !explored(x, y) {
+            continue;
         }
+        let color = if game.map.get(x, y) == Some(Tile::Wall) {
+            DARKGRAY
+        } else if game.map.get(x, y) == Some(Tile::StairsDown) {
+            WHITE
+        } else if game.gold.contains(&(x, y)) {
+            colors.gold
+        } else if game.potions.contains(&(x, y)) {
+            colors.potion
+        } else {
+            LIGHTGRAY
+        };
+        tile(x, y, tile_size, color);
     }
 
-    // Monsters
-    for &(mx, my) in data.monster_positions {
-        if data.explored[my][mx] {
-            let px = minimap_x + mx as f32 * tile_size;
-            let py = minimap_y + my as f32 * tile_size;
-            draw_rectangle(px, py, tile_size, tile_size, PURPLE);
+    for m in &game.monsters {
+        if explored(m.x, m.y) {
+            tile(m.x, m.y, tile_size, PURPLE);
         }
     }
 
-    // Player
-    let px = minimap_x + data.player_pos.0 as f32 * tile_size;
-    let py = minimap_y + data.player_pos.1 as f32 * tile_size;
-    draw_rectangle(px, py, tile_size * 1.5, tile_size * 1.5, BLUE);
+    tile(game.player.x, game.player.y, tile_size * 1.5, BLUE);
 
-    // Border
-    draw_rectangle_lines(minimap_x, minimap_y, minimap_size, minimap_size, 2., WHITE);
+    draw_rectangle_lines(area.x, area.y, area.w, area.h, ui.px(2.), WHITE);
 }
@@ -0,0 +1,104 @@
+use macroquad::prelude::*;
+
+use super::fill;
+use super::layout::{Anchor, Layout};
+use crate::systems::gamepad::{Gamepads, PadButton};
+
+/// Characters per row.
+const COLUMNS: usize = 10;
+
+/// Letters and digits, for player names.
+pub const NAME_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+/// Names plus a few separators, for save names.
+pub const SAVE_NAME_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 -.";
+/// Hex digits, for seeds.
+pub const SEED_CHARS: &str = "0123456789ABCDEF";
+
+/// What the player did on the keyboard this frame.
+pub enum KeyboardInput {
+    Char(char),
+    Backspace,
+    Done,
+}
+
+/// Grid of characters picked with a gamepad, followed by DEL and OK keys.
+/// A/South types the key under the cursor, B/East deletes and Start is OK.
+pub struct OnScreenKeyboard {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl OnScreenKeyboard {
+    pub fn new(chars: &str) -> Self {
+        OnScreenKeyboard {
+            chars: chars.chars().collect(),
+            cursor: 0,
+        }
+    }
+
+    /// Keys including DEL and OK.
+    fn len(&self) -> usize {
+        self.chars.len() + 2
+    }
+
+    fn label(&self, key: usize) -> String {
+        match self.chars.get(key) {
+            Some(' ') => "SPC".to_string(),
+            Some(c) => c.to_string(),
+            None if key == self.chars.len() => "DEL".to_string(),
+            None => "OK".to_string(),
+        }
+    }
+
+    /// Move the cursor and read presses.
+    pub fn update(&mut self, pad: &Gamepads) -> Option<KeyboardInput> {
+        if let Some((dx, dy)) = pad.movement() {
+            let len = self.len() as i32;
+            let mut cursor = self.cursor as i32 + dx;
+            // Up and down stop at the edges instead of wrapping
+            let row = cursor + dy * COLUMNS as i32;
+            if (0..len).contains(&row) {
+                cursor = row;
+            }
+            self.cursor = cursor.rem_euclid(len) as usize;
+        }
+
+        if pad.pressed(PadButton::Start) {
+            return Some(KeyboardInput::Done);
+        }
+        if pad.pressed(PadButton::East) {
+            return Some(KeyboardInput::Backspace);
+        }
+        if !pad.pressed(PadButton::South) {
+            return None;
+        }
+        Some(match self.chars.get(self.cursor) {
+            Some(c) => KeyboardInput::Char(*c),
+            None if self.cursor == self.chars.len() => KeyboardInput::Backspace,
+            None => KeyboardInput::Done,
+        })
+    }
+
+    /// Draw the grid centred `offset_y` reference pixels below the middle of
+    /// the screen.
+    pub fn draw(&self, ui: &Layout, offset_y: f32) {
+        let rows = self.len().div_ceil(COLUMNS) as f32;
+        let panel = ui.rect(
+            Anchor::Center,
+            vec2(COLUMNS as f32 * 44. + 20., rows * 34. + 10.),
+            vec2(0., offset_y + (rows - 1.) * 17.),
+        );
+        fill(panel, Color::new(0.9, 0.9, 0.9, 1.));
+        for key in 0..self.len() {
+            let (col, row) = (key % COLUMNS, key / COLUMNS);
+            let x = (col as f32 - (COLUMNS - 1) as f32 / 2.) * 44.;
+            let y = offset_y + row as f32 * 34.;
+            let (text, color) = if key == self.cursor {
+                (format!("[{}]", self.label(key)), RED)
+            } else {
+                (self.label(key), DARKGRAY)
+            };
+            ui.text(&text, Anchor::Center, vec2(x, y), 24., color);
+        }
+    }
+}
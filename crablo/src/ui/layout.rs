@@ -0,0 +1,144 @@
+//! Resolution-independent placement.
+//!
+//! Screens are laid out in reference pixels for an 800x600 window. A
+//! [`Layout`] scales those to the actual window and the player's UI scale,
+//! and places boxes relative to an [`Anchor`] on the screen, so nothing is
+//! positioned with raw `screen_width()` arithmetic.
+
+use macroquad::prelude::*;
+
+use crate::core::settings::{MAX_UI_SCALE, MIN_UI_SCALE};
+
+/// Window size the reference pixels are designed for.
+const REFERENCE_SIZE: (f32, f32) = (800., 600.);
+
+/// Gap kept between anchored boxes and the screen edge, in reference pixels.
+const PADDING: f32 = 10.;
+
+/// Point of the screen a box hangs off. The same point of the box is lined
+/// up with it, e.g. `BottomRight` puts the box in the bottom-right corner.
+#[derive(Clone, Copy)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Center,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Fraction of the free space to the left of and above the box.
+    fn factors(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0., 0.),
+            Anchor::Top => (0.5, 0.),
+            Anchor::TopRight => (1., 0.),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::BottomLeft => (0., 1.),
+            Anchor::Bottom => (0.5, 1.),
+            Anchor::BottomRight => (1., 1.),
+        }
+    }
+}
+
+/// Placement for the current frame.
+#[derive(Clone, Copy)]
+pub struct Layout {
+    scale: f32,
+}
+
+impl Layout {
+    /// Layout for the current window at the player's `ui_scale`.
+    pub fn new(ui_scale: f32) -> Self {
+        let fit = (screen_width() / REFERENCE_SIZE.0).min(screen_height() / REFERENCE_SIZE.1);
+        Layout {
+            scale: fit.max(MIN_UI_SCALE) * ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE),
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Reference pixels to screen pixels.
+    pub fn px(&self, length: f32) -> f32 {
+        length * self.scale
+    }
+
+    fn font_size(&self, size: f32) -> u16 {
+        self.px(size).round().max(1.) as u16
+    }
+
+    /// Size of `text` drawn at reference font `size`.
+    pub fn measure(&self, text: &str, size: f32) -> TextDimensions {
+        measure_text(text, None, self.font_size(size), 1.)
+    }
+
+    /// Top-left corner of a box of `size` screen pixels hung off `anchor`,
+    /// nudged by `offset` reference pixels.
+    pub fn place(&self, anchor: Anchor, size: Vec2, offset: Vec2) -> Vec2 {
+        let (fx, fy) = anchor.factors();
+        let pad = self.px(PADDING);
+        // Edges keep their padding; centred axes don't
+        let free_x = screen_width() - size.x - if fx == 0.5 { 0. } else { 2. * pad };
+        let free_y = screen_height() - size.y - if fy == 0.5 { 0. } else { 2. * pad };
+        vec2(
+            if fx == 0.5 { 0. } else { pad } + free_x * fx + self.px(offset.x),
+            if fy == 0.5 { 0. } else { pad } + free_y * fy + self.px(offset.y),
+        )
+    }
+
+    /// Screen rectangle of a box of `size` reference pixels hung off `anchor`.
+    pub fn rect(&self, anchor: Anchor, size: Vec2, offset: Vec2) -> Rect {
+        let size = size * self.scale;
+        let pos = self.place(anchor, size, offset);
+        Rect::new(pos.x, pos.y, size.x, size.y)
+    }
+
+    /// Draw `text` with its top-left corner at `pos` and return the area it
+    /// covers.
+    pub fn text_at(&self, text: &str, pos: Vec2, size: f32, color: Color) -> Rect {
+        let dims = self.measure(text, size);
+        draw_text_ex(
+            text,
+            pos.x,
+            pos.y + dims.offset_y,
+            TextParams {
+                font_size: self.font_size(size),
+                color,
+                ..Default::default()
+            },
+        );
+        Rect::new(pos.x, pos.y, dims.width, dims.height)
+    }
+
+    /// Draw `text` hung off `anchor` and return the area it covers.
+    pub fn text(&self, text: &str, anchor: Anchor, offset: Vec2, size: f32, color: Color) -> Rect {
+        let dims = self.measure(text, size);
+        let pos = self.place(anchor, vec2(dims.width, dims.height), offset);
+        self.text_at(text, pos, size, color)
+    }
+
+    /// Left edge of a block of lines drawn at `size`, hung off `anchor` as
+    /// one box as wide as its widest line, so the lines stay left-aligned.
+    pub fn block_x<'a>(
+        &self,
+        lines: impl IntoIterator<Item = &'a str>,
+        size: f32,
+        anchor: Anchor,
+        offset_x: f32,
+    ) -> f32 {
+        let width = lines
+            .into_iter()
+            .map(|line| self.measure(line, size).width)
+            .fold(0., f32::max);
+        self.place(anchor, vec2(width, 0.), vec2(offset_x, 0.)).x
+    }
+
+    /// Screen y of `offset_y` reference pixels below the anchor's top edge.
+    pub fn y(&self, anchor: Anchor, offset_y: f32) -> f32 {
+        self.place(anchor, Vec2::ZERO, vec2(0., offset_y)).y
+    }
+}
@@ -1,227 +1,246 @@
-use crate::core::constants::Difficulty;
-use crate::core::database::ScoreEntry;
 use macroquad::prelude::*;
 
-pub fn draw_menu(selected_difficulty: Difficulty, has_save: bool) {
-    draw_text("CRABLO", screen_width() / 2. - 80., 100., 60., DARKPURPLE);
-
-    draw_text(
-        "Select Difficulty:",
-        screen_width() / 2. - 100.,
-        180.,
-        28.,
-        BLACK,
-    );
-
-    let difficulties = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
-    for (i, diff) in difficulties.iter().enumerate() {
-        let y = 220. + (i as f32 * 40.);
-        let color = if *diff == selected_difficulty {
-            GOLD
-        } else {
-            DARKGRAY
-        };
-        let prefix = if *diff == selected_difficulty {
-            "> "
-        } else {
-            "  "
-        };
-        draw_text(
-            &format!("{}{}", prefix, diff.name()),
-            screen_width() / 2. - 60.,
-            y,
-            30.,
-            color,
-        );
+use super::hall_of_fame::HallOfFameScene;
+use super::keyboard::{KeyboardInput, OnScreenKeyboard, SEED_CHARS};
+use super::layout::Anchor;
+use super::play::PlayScene;
+use super::save_slots::{SaveSlotsScene, SlotMode};
+use super::settings::SettingsScene;
+use super::{draw_text_entry, App, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::core::constants::Difficulty;
+use crate::core::rng::parse_seed;
+use crate::core::storage::AUTOSAVE_SLOT;
+use crate::core::Game;
+use crate::systems::gamepad::PadButton;
+
+/// Main menu entries. Continue and Resume only show up when there is
+/// something to load.
+#[derive(Clone, Copy, PartialEq)]
+enum Entry {
+    Play,
+    Resume,
+    Continue,
+    Seed,
+    HallOfFame,
+    Settings,
+}
+
+impl Entry {
+    /// Entries on offer right now, top to bottom.
+    fn available(app: &App) -> Vec<Entry> {
+        let mut entries = vec![Entry::Play];
+        if app.interrupted_run.is_some() {
+            entries.push(Entry::Resume);
+        }
+        if app.storage.has_save().unwrap_or(false) {
+            entries.push(Entry::Continue);
+        }
+        entries.extend([Entry::Seed, Entry::HallOfFame, Entry::Settings]);
+        entries
     }
+}
+
+/// Title screen. Entries are picked from a list so the menu works the same
+/// with the keyboard and a gamepad.
+#[derive(Default)]
+pub struct MenuScene {
+    selected: usize,
+    /// Read from storage when the menu opens and whenever it is back on top.
+    entries: Vec<Entry>,
+}
 
-    draw_text(
-        "Use UP/DOWN to select, ENTER to start",
-        screen_width() / 2. - 180.,
-        380.,
-        20.,
-        GRAY,
-    );
-
-    draw_text(
-        "Press H for Hall of Fame",
-        screen_width() / 2. - 110.,
-        410.,
-        20.,
-        GRAY,
-    );
-
-    if has_save {
-        draw_text(
-            "Press C to CONTINUE saved game",
-            screen_width() / 2. - 140.,
-            440.,
+impl Scene for MenuScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if self.entries.is_empty() {
+            self.entries = Entry::available(app);
+        }
+        let entries = &self.entries;
+        if app.menu_pressed(Action::MoveUp) {
+            self.selected = (self.selected + entries.len() - 1) % entries.len();
+        }
+        if app.menu_pressed(Action::MoveDown) {
+            self.selected = (self.selected + 1) % entries.len();
+        }
+        self.selected = self.selected.min(entries.len() - 1);
+        let entry = entries[self.selected];
+
+        if entry == Entry::Play {
+            if app.menu_pressed(Action::MoveLeft) {
+                app.selected_difficulty = match app.selected_difficulty {
+                    Difficulty::Normal => Difficulty::Easy,
+                    Difficulty::Hard => Difficulty::Normal,
+                    Difficulty::Easy => Difficulty::Easy,
+                };
+            }
+            if app.menu_pressed(Action::MoveRight) {
+                app.selected_difficulty = match app.selected_difficulty {
+                    Difficulty::Easy => Difficulty::Normal,
+                    Difficulty::Normal => Difficulty::Hard,
+                    Difficulty::Hard => Difficulty::Hard,
+                };
+            }
+        }
+
+        if !app.menu_pressed(Action::Confirm) {
+            return Transition::None;
+        }
+        match entry {
+            Entry::Play => {
+                let difficulty = app.selected_difficulty;
+                app.confirm_discard(move |app| {
+                    app.start_run(Game::with_difficulty(difficulty));
+                    Transition::Reset(Box::new(PlayScene))
+                })
+            }
+            Entry::Resume => {
+                app.interrupted_run = None;
+                if app.load_run(AUTOSAVE_SLOT) {
+                    Transition::Reset(Box::new(PlayScene))
+                } else {
+                    Transition::None
+                }
+            }
+            Entry::Continue => Transition::Push(Box::new(SaveSlotsScene::new(app, SlotMode::Load))),
+            Entry::Seed => Transition::Push(Box::new(SeedScene::default())),
+            Entry::HallOfFame => Transition::Reset(Box::new(HallOfFameScene::new(app))),
+            Entry::Settings => Transition::Push(Box::new(SettingsScene::default())),
+        }
+    }
+
+    fn resume(&mut self, app: &mut App) {
+        self.entries = Entry::available(app);
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        ui.text("CRABLO", Anchor::Top, vec2(0., 45.), 60., DARKPURPLE);
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let (label, color) = match entry {
+                Entry::Play => (
+                    format!("Play  < {} >", app.selected_difficulty.name()),
+                    DARKGRAY,
+                ),
+                Entry::Resume => (
+                    app.interrupted_run.as_ref().map_or(String::new(), |run| {
+                        format!(
+                            "Resume interrupted run (Floor {}, Lv {})",
+                            run.floor, run.level
+                        )
+                    }),
+                    ORANGE,
+                ),
+                Entry::Continue => ("Continue a saved game".to_string(), DARKGREEN),
+                Entry::Seed => ("Play a seed".to_string(), DARKGRAY),
+                Entry::HallOfFame => ("Hall of Fame".to_string(), DARKGRAY),
+                Entry::Settings => ("Settings".to_string(), DARKGRAY),
+            };
+            let (text, color) = if i == self.selected {
+                (format!("> {} <", label), GOLD)
+            } else {
+                (label, color)
+            };
+            ui.text(
+                &text,
+                Anchor::Top,
+                vec2(0., 160. + i as f32 * 45.),
+                30.,
+                color,
+            );
+        }
+
+        ui.text(
+            &format!(
+                "UP/DOWN select, LEFT/RIGHT difficulty, {} to choose",
+                app.hint(Action::Confirm)
+            ),
+            Anchor::Bottom,
+            vec2(0., -30.),
             20.,
-            GREEN,
+            GRAY,
         );
+
+        for (i, warning) in app.warnings.iter().rev().enumerate() {
+            ui.text(
+                warning,
+                Anchor::BottomLeft,
+                vec2(0., -20. * i as f32),
+                18.,
+                RED,
+            );
+        }
     }
 }
 
-pub fn draw_pause() {
-    draw_rectangle(
-        0.,
-        0.,
-        screen_width(),
-        screen_height(),
-        Color::new(0., 0., 0., 0.7),
-    );
-
-    draw_text(
-        "PAUSED",
-        screen_width() / 2. - 80.,
-        screen_height() / 2. - 50.,
-        60.,
-        WHITE,
-    );
-
-    draw_text(
-        "Press ESC or P to resume",
-        screen_width() / 2. - 120.,
-        screen_height() / 2. + 20.,
-        24.,
-        LIGHTGRAY,
-    );
-
-    draw_text(
-        "Press Q to quit to menu",
-        screen_width() / 2. - 110.,
-        screen_height() / 2. + 50.,
-        24.,
-        LIGHTGRAY,
-    );
-
-    draw_text(
-        "Press S to SAVE and quit",
-        screen_width() / 2. - 115.,
-        screen_height() / 2. + 80.,
-        24.,
-        GREEN,
-    );
+/// Typing a seed to play, on the difficulty picked in the menu.
+pub struct SeedScene {
+    input: String,
+    keyboard: OnScreenKeyboard,
 }
 
-pub fn draw_game_over(score: i32, floor: i32) {
-    draw_rectangle(
-        0.,
-        0.,
-        screen_width(),
-        screen_height(),
-        Color::new(1., 1., 1., 0.7),
-    );
-
-    draw_text(
-        "GAME OVER",
-        screen_width() / 2. - 120.,
-        screen_height() / 2. - 50.,
-        50.,
-        RED,
-    );
-
-    draw_text(
-        &format!("Score: {} | Floor: {}", score, floor),
-        screen_width() / 2. - 100.,
-        screen_height() / 2.,
-        24.,
-        BLACK,
-    );
-
-    draw_text(
-        "Press ENTER to save score",
-        screen_width() / 2. - 110.,
-        screen_height() / 2. + 40.,
-        20.,
-        DARKGRAY,
-    );
-
-    draw_text(
-        "Press H for Hall of Fame",
-        screen_width() / 2. - 100.,
-        screen_height() / 2. + 70.,
-        20.,
-        DARKGRAY,
-    );
+impl Default for SeedScene {
+    fn default() -> Self {
+        SeedScene {
+            input: String::new(),
+            keyboard: OnScreenKeyboard::new(SEED_CHARS),
+        }
+    }
 }
 
-pub fn draw_enter_name(player_name: &str) {
-    draw_rectangle(
-        0.,
-        0.,
-        screen_width(),
-        screen_height(),
-        Color::new(0.9, 0.9, 0.9, 1.),
-    );
-
-    draw_text(
-        "Enter your name:",
-        screen_width() / 2. - 100.,
-        screen_height() / 2. - 50.,
-        30.,
-        BLACK,
-    );
-
-    draw_text(
-        &format!("{}_", player_name),
-        screen_width() / 2. - 80.,
-        screen_height() / 2.,
-        40.,
-        DARKBLUE,
-    );
-
-    draw_text(
-        "Press ENTER to confirm",
-        screen_width() / 2. - 100.,
-        screen_height() / 2. + 50.,
-        20.,
-        GRAY,
-    );
-}
+impl Scene for SeedScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let mut typed = get_char_pressed();
+        let mut done = is_key_pressed(KeyCode::Enter);
+        match self.keyboard.update(&app.pad) {
+            Some(KeyboardInput::Char(c)) => typed = Some(c),
+            Some(KeyboardInput::Backspace) => {
+                self.input.pop();
+            }
+            Some(KeyboardInput::Done) => done = true,
+            None => {}
+        }
+        // Seeds are shown in hex, so only accept hex digits
+        if let Some(c) = typed {
+            if c.is_ascii_hexdigit() && self.input.len() < 16 {
+                self.input.push(c.to_ascii_uppercase());
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+
+        // B deletes on the keyboard, so Back cancels instead
+        if is_key_pressed(KeyCode::Escape) || app.pad.pressed(PadButton::Select) {
+            return Transition::Pop;
+        }
+        if done {
+            if let Some(seed) = parse_seed(&self.input) {
+                let difficulty = app.selected_difficulty;
+                return app.confirm_discard(move |app| {
+                    app.start_run(Game::with_seed(difficulty, seed));
+                    Transition::Reset(Box::new(PlayScene))
+                });
+            }
+        }
+        Transition::None
+    }
 
-pub fn draw_hall_of_fame(scores: &[ScoreEntry]) {
-    draw_rectangle(
-        0.,
-        0.,
-        screen_width(),
-        screen_height(),
-        Color::new(0.1, 0.1, 0.2, 1.),
-    );
-
-    draw_text("HALL OF FAME", screen_width() / 2. - 120., 80., 50., GOLD);
-
-    for (i, entry) in scores.iter().enumerate() {
-        let y = 140. + (i as f32 * 35.);
-        let color = match i {
-            0 => GOLD,
-            1 => LIGHTGRAY,
-            2 => ORANGE,
-            _ => WHITE,
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        let hint = if app.pad.connected() {
+            "Start to play, Back to go back"
+        } else {
+            "Press ENTER to start, ESC to go back"
         };
-
-        draw_text(
-            &format!("{}. {} - {}", entry.rank, entry.name, entry.score),
-            screen_width() / 2. - 150.,
-            y,
-            28.,
-            color,
+        draw_text_entry(
+            &ui,
+            &format!("Enter a seed ({}):", app.selected_difficulty.name()),
+            &self.input,
+            hint,
         );
+        if app.pad.connected() {
+            self.keyboard.draw(&ui, 110.);
+        }
     }
-
-    draw_text(
-        "Press ENTER to return to menu",
-        screen_width() / 2. - 140.,
-        screen_height() - 70.,
-        20.,
-        GRAY,
-    );
-
-    draw_text(
-        "Press R to RESET all scores",
-        screen_width() / 2. - 130.,
-        screen_height() - 40.,
-        20.,
-        RED,
-    );
 }
@@ -1,7 +1,437 @@
+//! Screens and overlays.
+//!
+//! Every screen is a [`Scene`] kept on a [`SceneStack`]. Only the top scene
+//! handles input; overlays such as the pause menu, the shop and confirmation
+//! dialogs are drawn over the scenes beneath them.
+//!
+//! - [`layout`] - Anchored, scaled placement used by every screen
+//! - [`menu`] - Main menu and seed entry
+//! - [`play`] - The running game
+//! - [`hud`] - In-game HUD and minimap
+//! - [`shop_ui`] - Shop overlay
+//! - [`pause`] - Pause overlay
+//! - [`settings`] - Settings screen
+//! - [`controls`] - Key rebinding
+//! - [`save_slots`] - Save slot picker and save naming
+//! - [`dialog`] - Yes/no confirmation overlay
+//! - [`keyboard`] - On-screen keyboard for typing with a gamepad
+//! - [`game_over`] - Game over screen and name entry
+//! - [`hall_of_fame`] - High scores and score details
+//! - [`replay`] - Replay playback
+
+pub mod controls;
+pub mod dialog;
+pub mod game_over;
+pub mod hall_of_fame;
 pub mod hud;
+pub mod keyboard;
+pub mod layout;
 pub mod menu;
+pub mod pause;
+pub mod play;
+pub mod replay;
+pub mod save_slots;
+pub mod settings;
 pub mod shop_ui;
 
-pub use hud::draw_hud;
-pub use menu::{draw_enter_name, draw_game_over, draw_hall_of_fame, draw_menu, draw_pause};
-pub use shop_ui::draw_shop;
+pub use menu::MenuScene;
+
+use std::path::PathBuf;
+
+use macroquad::prelude::*;
+
+use crate::core::bindings::Action;
+use crate::core::constants::Difficulty;
+use crate::core::settings::Settings;
+use crate::core::storage::{interrupted_run, SaveSlot, Storage, AUTOSAVE_SLOT};
+use crate::core::Game;
+use crate::systems::game_renderer::RenderStyle;
+use crate::systems::gamepad::{Gamepads, PadButton};
+use crate::systems::input::{action_pressed, pad_button};
+use crate::systems::GameAudio;
+use dialog::ConfirmScene;
+use layout::{Anchor, Layout};
+use play::SimClock;
+
+/// How long a notice stays on screen, in seconds.
+const NOTICE_DURATION: f32 = 4.;
+
+/// A message drawn on top of every screen for a few seconds, used to report
+/// persistence failures.
+struct Notice {
+    text: String,
+    time_left: f32,
+}
+
+/// Everything the scenes share.
+pub struct App {
+    pub game: Game,
+    pub storage: Box<dyn Storage>,
+    pub audio: GameAudio,
+    pub pad: Gamepads,
+    /// Shown on the menu when storage or the monster roster couldn't be
+    /// loaded.
+    pub warnings: Vec<String>,
+    /// Autosave left behind by a session that didn't exit cleanly.
+    pub interrupted_run: Option<SaveSlot>,
+    pub selected_difficulty: Difficulty,
+    /// Change through [`App::change_settings`] so they take effect.
+    pub settings: Settings,
+    settings_path: PathBuf,
+    /// Fixed-step clock driving `game`.
+    pub clock: SimClock,
+    notice: Option<Notice>,
+}
+
+impl App {
+    /// Start a session on `storage`, noting any run the last one left behind,
+    /// with the settings kept at `settings_path`.
+    pub fn new(
+        mut storage: Box<dyn Storage>,
+        warnings: Vec<String>,
+        mut audio: GameAudio,
+        settings_path: PathBuf,
+    ) -> Self {
+        let interrupted_run = interrupted_run(&mut *storage);
+        // The window was already created with the display settings
+        let (settings, notice) = match Settings::load(&settings_path) {
+            Ok(settings) => (settings, None),
+            Err(e) => (
+                Settings::default(),
+                Some(format!("Ignoring {} - using the default settings", e)),
+            ),
+        };
+        audio.set_volumes(settings.master_volume, settings.sfx_volume);
+        let mut app = App {
+            game: Game::new(),
+            storage,
+            audio,
+            pad: Gamepads::new(),
+            warnings,
+            interrupted_run,
+            selected_difficulty: Difficulty::Normal,
+            settings,
+            settings_path,
+            clock: SimClock::default(),
+            notice: None,
+        };
+        if let Some(notice) = notice {
+            app.notify(notice);
+        }
+        app
+    }
+
+    /// Layout for this frame.
+    pub fn layout(&self) -> Layout {
+        Layout::new(self.settings.ui_scale)
+    }
+
+    /// Whether a key or gamepad button for `action` was pressed this frame.
+    pub fn pressed(&self, action: Action) -> bool {
+        action_pressed(&self.settings.bindings, &self.pad, action)
+    }
+
+    /// Like [`App::pressed`], but the arrow keys and Enter always work too so
+    /// a bad binding can't lock the player out of the menus, and the left
+    /// stick and D-pad navigate.
+    pub fn menu_pressed(&self, action: Action) -> bool {
+        let (fallback, pad_dir) = match action {
+            Action::MoveUp => (Some(KeyCode::Up), Some((0, -1))),
+            Action::MoveDown => (Some(KeyCode::Down), Some((0, 1))),
+            Action::MoveLeft => (Some(KeyCode::Left), Some((-1, 0))),
+            Action::MoveRight => (Some(KeyCode::Right), Some((1, 0))),
+            Action::Confirm => (Some(KeyCode::Enter), None),
+            _ => (None, None),
+        };
+        self.pressed(action)
+            || fallback.is_some_and(is_key_pressed)
+            || pad_dir.is_some_and(|dir| self.pad_moved(dir))
+    }
+
+    /// Whether the left stick or D-pad stepped towards the screen direction
+    /// `dir` this frame. Diagonals count for both of their directions.
+    pub fn pad_moved(&self, (x, y): (i32, i32)) -> bool {
+        self.pad
+            .movement()
+            .is_some_and(|(mx, my)| (x != 0 && mx == x) || (y != 0 && my == y))
+    }
+
+    /// Whether the player asked to leave the current screen.
+    pub fn back_pressed(&self) -> bool {
+        is_key_pressed(KeyCode::Escape) || self.pad.pressed(PadButton::East)
+    }
+
+    /// What to press for `action`: the gamepad button while a pad is
+    /// connected, otherwise the first bound key.
+    pub fn hint(&self, action: Action) -> String {
+        match pad_button(action).filter(|_| self.pad.connected()) {
+            Some(button) => button.name().to_string(),
+            None => self.settings.bindings.primary_label(action),
+        }
+    }
+
+    /// How the world is drawn this frame.
+    pub fn render_style(&self) -> RenderStyle {
+        RenderStyle {
+            text_scale: self.layout().scale(),
+            shake: self.settings.screen_shake,
+            colors: self.settings.palette.colors(),
+        }
+    }
+
+    /// Switch to `settings` straight away. Vsync waits for a restart.
+    pub fn change_settings(&mut self, settings: Settings) {
+        let settings = settings.clamped();
+        if settings.fullscreen != self.settings.fullscreen {
+            set_fullscreen(settings.fullscreen);
+        }
+        self.audio
+            .set_volumes(settings.master_volume, settings.sfx_volume);
+        self.settings = settings;
+    }
+
+    /// Write the settings to disk.
+    pub fn save_settings(&mut self) {
+        if let Err(e) = self.settings.save(&self.settings_path) {
+            self.notify(format!("Couldn't save settings: {}", e));
+        }
+    }
+
+    /// Show `text` on top of the screen for a few seconds.
+    pub fn notify(&mut self, text: String) {
+        self.notice = Some(Notice {
+            text,
+            time_left: NOTICE_DURATION,
+        });
+    }
+
+    /// Run `start` straight away, or once the player agrees to lose the run
+    /// a crashed session left in the autosave slot, which starting or
+    /// loading another run overwrites. Declining, or a `start` that stays
+    /// put, closes the question.
+    pub fn confirm_discard(
+        &mut self,
+        start: impl FnOnce(&mut App) -> Transition + 'static,
+    ) -> Transition {
+        if self.interrupted_run.is_none() {
+            return start(self);
+        }
+        Transition::Push(Box::new(ConfirmScene::new(
+            "Discard the interrupted run?".to_string(),
+            |app| match start(app) {
+                Transition::None => Transition::Pop,
+                transition => transition,
+            },
+        )))
+    }
+
+    /// Make `game` the current run and autosave it straight away.
+    pub fn start_run(&mut self, game: Game) {
+        self.game = game;
+        self.interrupted_run = None;
+        self.autosave();
+    }
+
+    /// Resume the run saved in `slot`. Returns whether it was loaded.
+    pub fn load_run(&mut self, slot: i32) -> bool {
+        match self.storage.load_game(slot) {
+            Ok(Some(save)) => match Game::from_save(&save) {
+                Ok(game) => {
+                    self.start_run(game);
+                    return true;
+                }
+                Err(e) => self.notify(format!("Save is corrupt: {}", e)),
+            },
+            Ok(None) => {}
+            Err(e) => self.notify(format!("Couldn't load save: {}", e)),
+        }
+        false
+    }
+
+    /// Write the current run into the autosave slot.
+    pub fn autosave(&mut self) {
+        let saved = self
+            .game
+            .to_save()
+            .map_err(|e| e.to_string())
+            .and_then(|mut save| {
+                save.name = "Autosave".to_string();
+                self.storage
+                    .save_game(AUTOSAVE_SLOT, &save)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = saved {
+            self.notify(format!("Autosave failed: {}", e));
+        }
+    }
+
+    /// Close the session cleanly, autosaving first if a run is in progress.
+    /// Returns whether it was; otherwise the next launch would take this one
+    /// for a crash, so the failure is shown.
+    pub fn close(&mut self, in_run: bool) -> bool {
+        if in_run {
+            self.autosave();
+        }
+        match self.storage.end_session() {
+            Ok(()) => true,
+            Err(e) => {
+                self.notify(format!(
+                    "Couldn't end the session: {} - close again to quit",
+                    e
+                ));
+                false
+            }
+        }
+    }
+
+    /// Draw the current notice, if any, and count down its time.
+    pub fn draw_notice(&mut self) {
+        let ui = self.layout();
+        if let Some(current) = self.notice.as_mut() {
+            current.time_left -= get_frame_time();
+            let dims = ui.measure(&current.text, 20.);
+            let panel = ui.rect(
+                Anchor::Top,
+                vec2(dims.width / ui.scale() + 20., 30.),
+                Vec2::ZERO,
+            );
+            fill(panel, Color::new(0.6, 0., 0., 0.9));
+            ui.text(
+                &current.text,
+                Anchor::Top,
+                vec2(0., 15. - dims.height / ui.scale() / 2.),
+                20.,
+                WHITE,
+            );
+            if current.time_left <= 0. {
+                self.notice = None;
+            }
+        }
+    }
+}
+
+/// What the scene stack should do after a scene's update.
+pub enum Transition {
+    /// Stay on the current scene.
+    None,
+    /// Open a scene on top of the current one.
+    Push(Box<dyn Scene>),
+    /// Close the current scene, returning to the one beneath it.
+    Pop,
+    /// Swap the current scene for another.
+    Replace(Box<dyn Scene>),
+    /// Close every scene and start over from this one.
+    Reset(Box<dyn Scene>),
+}
+
+/// A screen or overlay.
+pub trait Scene {
+    /// Handle this frame's input.
+    fn update(&mut self, app: &mut App) -> Transition;
+
+    fn draw(&self, app: &App);
+
+    /// Called when the scene is back on top after the one above it was
+    /// popped, e.g. to reload what a dialog may have changed.
+    fn resume(&mut self, _app: &mut App) {}
+
+    /// Overlays are drawn over the scene beneath them instead of hiding it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Whether this scene holds a run that should be autosaved when the
+    /// window closes.
+    fn in_run(&self) -> bool {
+        false
+    }
+}
+
+/// Open scenes, bottom to top. Never empty.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+}
+
+impl SceneStack {
+    pub fn new(root: Box<dyn Scene>) -> Self {
+        SceneStack { scenes: vec![root] }
+    }
+
+    /// Update the top scene and apply the transition it asks for.
+    pub fn update(&mut self, app: &mut App) {
+        let Some(top) = self.scenes.last_mut() else {
+            return;
+        };
+        let transition = top.update(app);
+        if !matches!(transition, Transition::None) {
+            // Characters typed on one scene shouldn't spill into the next
+            clear_input_queue();
+        }
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                if self.scenes.len() > 1 {
+                    self.scenes.pop();
+                }
+                if let Some(top) = self.scenes.last_mut() {
+                    top.resume(app);
+                }
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scene) => {
+                self.scenes.clear();
+                self.scenes.push(scene);
+            }
+        }
+    }
+
+    /// Draw the top scene and, below overlays, the scenes they cover.
+    pub fn draw(&self, app: &App) {
+        let base = self
+            .scenes
+            .iter()
+            .rposition(|s| !s.is_overlay())
+            .unwrap_or(0);
+        for scene in &self.scenes[base..] {
+            scene.draw(app);
+        }
+    }
+
+    pub fn in_run(&self) -> bool {
+        self.scenes.iter().any(|s| s.in_run())
+    }
+}
+
+/// Format seconds as `m:ss`.
+pub fn format_duration(secs: i32) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Dim everything drawn so far.
+fn draw_dim(color: Color) {
+    draw_rectangle(0., 0., screen_width(), screen_height(), color);
+}
+
+/// Fill `rect` with `color`.
+fn fill(rect: Rect, color: Color) {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+}
+
+/// Full-screen text field with a prompt above and a hint below, used for
+/// names and seeds.
+fn draw_text_entry(ui: &Layout, prompt: &str, input: &str, hint: &str) {
+    draw_dim(Color::new(0.9, 0.9, 0.9, 1.));
+    ui.text(prompt, Anchor::Center, vec2(0., -60.), 30., BLACK);
+    ui.text(
+        &format!("{}_", input),
+        Anchor::Center,
+        vec2(0., 0.),
+        40.,
+        DARKBLUE,
+    );
+    ui.text(hint, Anchor::Center, vec2(0., 55.), 20., GRAY);
+}
@@ -0,0 +1,75 @@
+use macroquad::prelude::*;
+
+use super::layout::Anchor;
+use super::save_slots::{SaveSlotsScene, SlotMode};
+use super::settings::SettingsScene;
+use super::{draw_dim, App, MenuScene, Scene, Transition};
+use crate::core::bindings::Action;
+
+/// Pause menu entries, top to bottom.
+const ITEMS: [&str; 4] = ["Resume", "Save game", "Settings", "Quit to menu"];
+
+/// Pause overlay over the running game. Entries are picked from a list
+/// rather than by letter so none of them can clash with a game binding.
+#[derive(Default)]
+pub struct PauseScene {
+    selected: usize,
+}
+
+impl Scene for PauseScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if app.pressed(Action::Pause) || app.back_pressed() {
+            return Transition::Pop;
+        }
+        if app.menu_pressed(Action::MoveUp) {
+            self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+        }
+        if app.menu_pressed(Action::MoveDown) {
+            self.selected = (self.selected + 1) % ITEMS.len();
+        }
+        if !app.menu_pressed(Action::Confirm) {
+            return Transition::None;
+        }
+        match self.selected {
+            0 => Transition::Pop,
+            1 => Transition::Push(Box::new(SaveSlotsScene::new(app, SlotMode::Save))),
+            2 => Transition::Push(Box::new(SettingsScene::default())),
+            _ => Transition::Reset(Box::new(MenuScene::default())),
+        }
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        draw_dim(Color::new(0., 0., 0., 0.7));
+        ui.text("PAUSED", Anchor::Center, vec2(0., -90.), 60., WHITE);
+        for (i, item) in ITEMS.iter().enumerate() {
+            let (text, color) = if i == self.selected {
+                (format!("> {} <", item), YELLOW)
+            } else {
+                (item.to_string(), LIGHTGRAY)
+            };
+            ui.text(
+                &text,
+                Anchor::Center,
+                vec2(0., -10. + i as f32 * 34.),
+                26.,
+                color,
+            );
+        }
+        ui.text(
+            &format!(
+                "UP/DOWN select, {} to choose, {} to resume",
+                app.hint(Action::Confirm),
+                app.hint(Action::Pause)
+            ),
+            Anchor::Bottom,
+            vec2(0., -20.),
+            20.,
+            GRAY,
+        );
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,109 @@
+use macroquad::prelude::*;
+
+use super::game_over::GameOverScene;
+use super::hud::draw_hud;
+use super::pause::PauseScene;
+use super::shop_ui::ShopScene;
+use super::{App, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::core::command::PlayerCommand;
+use crate::core::constants::SIM_DT;
+use crate::core::storage::AUTOSAVE_SLOT;
+use crate::systems::input::poll_commands;
+
+/// Accumulates frame time so the simulation runs at a fixed step, keeping
+/// recorded replays in sync.
+#[derive(Default)]
+pub struct SimClock {
+    time: f32,
+    /// Input not yet consumed by a step, so no key press is lost.
+    pending: Vec<PlayerCommand>,
+}
+
+impl SimClock {
+    /// Have `command` run on the next step.
+    pub(super) fn queue(&mut self, command: PlayerCommand) {
+        if !self.pending.contains(&command) {
+            self.pending.push(command);
+        }
+    }
+}
+
+/// Feed this frame's input to the current run and step it, reacting to what
+/// happened. Used by every scene that keeps the run going.
+pub(super) fn advance(app: &mut App) -> Transition {
+    for command in poll_commands(&app.game, &app.settings.bindings, &app.pad) {
+        app.clock.queue(command);
+    }
+
+    app.clock.time += get_frame_time();
+    while app.clock.time >= SIM_DT {
+        app.clock.time -= SIM_DT;
+        let events = app.game.step(&app.clock.pending, SIM_DT);
+        app.clock.pending.clear();
+
+        // Play sounds based on events
+        if events.player_hit {
+            app.audio.play_hit();
+        }
+        if events.gold_collected {
+            app.audio.play_gold();
+        }
+        if events.monster_killed {
+            app.audio.play_death();
+        }
+        if events.floor_completed {
+            app.audio.play_level_complete();
+        }
+        // Autosave between floors so a crash costs at most one, and after
+        // shopping so purchases are kept
+        if events.floor_completed || events.shop_closed {
+            app.autosave();
+        }
+
+        if events.game_over {
+            // A finished run can't be resumed
+            if let Err(e) = app.storage.delete_save(AUTOSAVE_SLOT) {
+                app.notify(format!("Couldn't clear the autosave: {}", e));
+            }
+            if events.victory {
+                app.audio.play_level_complete();
+            } else {
+                app.audio.play_game_over();
+            }
+            return Transition::Reset(Box::new(GameOverScene));
+        }
+    }
+    Transition::None
+}
+
+/// The run in progress.
+pub struct PlayScene;
+
+impl Scene for PlayScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if !app.game.in_shop && app.pressed(Action::Pause) {
+            return Transition::Push(Box::new(PauseScene::default()));
+        }
+
+        let transition = advance(app);
+        app.game
+            .update_camera(get_frame_time(), (screen_width(), screen_height()));
+        match transition {
+            Transition::None if app.game.in_shop => {
+                Transition::Push(Box::new(ShopScene::default()))
+            }
+            transition => transition,
+        }
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        app.game.draw(app.render_style());
+        draw_hud(&ui, app, &app.game);
+    }
+
+    fn in_run(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,69 @@
+use macroquad::prelude::*;
+
+use super::hud::draw_hud;
+use super::layout::Anchor;
+use super::{App, Scene, Transition};
+use crate::core::constants::SIM_DT;
+use crate::core::replay::{Replay, ReplayPlayer};
+use crate::core::Game;
+
+/// Plays a recorded run back on its own game, leaving the current one be.
+pub struct ReplayScene {
+    game: Game,
+    player: ReplayPlayer,
+    sim_time: f32,
+}
+
+impl ReplayScene {
+    pub fn new(replay: Replay) -> Self {
+        ReplayScene {
+            game: Game::with_seed(replay.difficulty, replay.seed),
+            player: ReplayPlayer::new(replay),
+            sim_time: 0.,
+        }
+    }
+}
+
+impl Scene for ReplayScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if app.back_pressed() {
+            return Transition::Pop;
+        }
+
+        // Hold F to fast-forward
+        let speed = if is_key_down(KeyCode::F) { 4. } else { 1. };
+        self.sim_time += get_frame_time() * speed;
+        while self.sim_time >= SIM_DT && !self.player.is_finished() {
+            self.sim_time -= SIM_DT;
+            let commands = self.player.next_commands();
+            self.game.step(&commands, SIM_DT);
+        }
+        self.game
+            .update_camera(get_frame_time(), (screen_width(), screen_height()));
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        self.game.draw(app.render_style());
+        draw_hud(&ui, app, &self.game);
+
+        let status = if self.player.is_finished() {
+            "END OF REPLAY".to_string()
+        } else {
+            format!(
+                "REPLAY {}/{}",
+                self.player.frame(),
+                self.player.replay().frames
+            )
+        };
+        ui.text(&status, Anchor::TopLeft, vec2(10., 5.), 24., MAROON);
+        ui.text(
+            "Hold F to fast-forward, ESC to exit",
+            Anchor::TopLeft,
+            vec2(10., 32.),
+            18.,
+            GRAY,
+        );
+    }
+}
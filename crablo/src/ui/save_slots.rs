@@ -0,0 +1,269 @@
+use macroquad::prelude::*;
+
+use super::dialog::{draw_prompt, ConfirmScene};
+use super::keyboard::{KeyboardInput, OnScreenKeyboard, SAVE_NAME_CHARS};
+use super::layout::Anchor;
+use super::play::PlayScene;
+use super::{draw_dim, format_duration, App, MenuScene, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::core::storage::{SaveSlot, AUTOSAVE_SLOT, SAVE_SLOTS};
+use crate::systems::gamepad::PadButton;
+
+/// Whether the slot picker was opened to load a run or to save one.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SlotMode {
+    Load,
+    Save,
+}
+
+/// Slot picker showing what each slot holds.
+pub struct SaveSlotsScene {
+    mode: SlotMode,
+    selected: i32,
+    slots: Vec<SaveSlot>,
+}
+
+impl SaveSlotsScene {
+    pub fn new(app: &App, mode: SlotMode) -> Self {
+        let mut scene = SaveSlotsScene {
+            mode,
+            selected: Self::first_slot(mode),
+            slots: vec![],
+        };
+        scene.refresh(app);
+        scene
+    }
+
+    fn refresh(&mut self, app: &App) {
+        self.slots = app.storage.list_saves().unwrap_or_default();
+    }
+
+    /// The autosave can be loaded but not written by hand.
+    fn first_slot(mode: SlotMode) -> i32 {
+        match mode {
+            SlotMode::Load => AUTOSAVE_SLOT,
+            SlotMode::Save => 1,
+        }
+    }
+
+    fn slot(&self, slot: i32) -> Option<&SaveSlot> {
+        self.slots.iter().find(|s| s.slot == slot)
+    }
+}
+
+impl Scene for SaveSlotsScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let first = Self::first_slot(self.mode);
+        let count = SAVE_SLOTS - first + 1;
+        let slot = self.selected;
+        // Name of the save in the selected slot, if it is filled
+        let current = self.slot(slot).map(|s| s.name.clone());
+
+        if app.menu_pressed(Action::MoveUp) {
+            self.selected = first + (slot - first - 1).rem_euclid(count);
+        }
+        if app.menu_pressed(Action::MoveDown) {
+            self.selected = first + (slot - first + 1) % count;
+        }
+
+        if (is_key_pressed(KeyCode::D) || app.pad.pressed(PadButton::North)) && current.is_some() {
+            return Transition::Push(Box::new(ConfirmScene::new(
+                format!("Delete slot {}?", slot),
+                move |app| {
+                    if let Err(e) = app.storage.delete_save(slot) {
+                        app.notify(format!("Couldn't delete save: {}", e));
+                    }
+                    Transition::Pop
+                },
+            )));
+        }
+
+        if app.menu_pressed(Action::Confirm) {
+            match (self.mode, current) {
+                (SlotMode::Load, Some(_)) => {
+                    let load = move |app: &mut App| {
+                        if app.load_run(slot) {
+                            Transition::Reset(Box::new(PlayScene))
+                        } else {
+                            Transition::None
+                        }
+                    };
+                    // Loading the autosave is resuming it
+                    return if slot == AUTOSAVE_SLOT {
+                        load(app)
+                    } else {
+                        app.confirm_discard(load)
+                    };
+                }
+                (SlotMode::Load, None) => {}
+                (SlotMode::Save, Some(name)) => {
+                    return Transition::Push(Box::new(ConfirmScene::new(
+                        format!("Overwrite slot {}?", slot),
+                        move |_| Transition::Replace(Box::new(SaveNameScene::new(slot, name))),
+                    )));
+                }
+                (SlotMode::Save, None) => {
+                    let name = format!("{} - Floor {}", app.game.difficulty.name(), app.game.floor);
+                    return Transition::Push(Box::new(SaveNameScene::new(slot, name)));
+                }
+            }
+        }
+
+        if app.back_pressed() {
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn resume(&mut self, app: &mut App) {
+        self.refresh(app);
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        draw_dim(Color::new(0.1, 0.1, 0.2, 1.));
+
+        let title = match self.mode {
+            SlotMode::Load => "LOAD GAME",
+            SlotMode::Save => "SAVE GAME",
+        };
+        ui.text(title, Anchor::Top, vec2(0., 40.), 50., GOLD);
+
+        // Rows share one left edge, as wide as the longest detail line
+        let left = ui.rect(Anchor::Top, vec2(500., 0.), Vec2::ZERO).x;
+        let first = Self::first_slot(self.mode);
+        for slot in first..=SAVE_SLOTS {
+            let y = ui.y(Anchor::Top, 115. + (slot - first) as f32 * 60.);
+            let selected = slot == self.selected;
+            let marker = if selected { ">" } else { " " };
+            match self.slot(slot) {
+                Some(save) => {
+                    let name = if save.name.is_empty() {
+                        format!("Slot {}", slot)
+                    } else {
+                        save.name.clone()
+                    };
+                    ui.text_at(
+                        &format!("{} {}. {}", marker, slot, name),
+                        vec2(left, y),
+                        26.,
+                        if selected { YELLOW } else { WHITE },
+                    );
+                    ui.text_at(
+                        &format!(
+                            "Floor {}  Lv {}  {}g  {}  {}  {}",
+                            save.floor,
+                            save.level,
+                            save.gold,
+                            save.difficulty.map_or("-", |d| d.name()),
+                            format_duration(save.playtime),
+                            save.saved_at
+                        ),
+                        vec2(left + ui.px(30.), y + ui.px(28.)),
+                        18.,
+                        LIGHTGRAY,
+                    );
+                }
+                None => {
+                    ui.text_at(
+                        &format!("{} {}. - empty -", marker, slot),
+                        vec2(left, y),
+                        26.,
+                        if selected { YELLOW } else { GRAY },
+                    );
+                }
+            }
+        }
+
+        let hint = match (self.mode, app.pad.connected()) {
+            (SlotMode::Load, false) => "ENTER: Load  D: Delete  ESC: Back",
+            (SlotMode::Save, false) => "ENTER: Save  D: Delete  ESC: Back",
+            (SlotMode::Load, true) => "A: Load  Y: Delete  B: Back",
+            (SlotMode::Save, true) => "A: Save  Y: Delete  B: Back",
+        };
+        ui.text(hint, Anchor::Bottom, vec2(0., -20.), 24., GRAY);
+    }
+}
+
+/// Naming a save before it is written into `slot`.
+pub struct SaveNameScene {
+    slot: i32,
+    name: String,
+    keyboard: OnScreenKeyboard,
+}
+
+impl SaveNameScene {
+    pub fn new(slot: i32, name: String) -> Self {
+        SaveNameScene {
+            slot,
+            name,
+            keyboard: OnScreenKeyboard::new(SAVE_NAME_CHARS),
+        }
+    }
+}
+
+impl Scene for SaveNameScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let mut typed: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+        let mut done = is_key_pressed(KeyCode::Enter);
+        match self.keyboard.update(&app.pad) {
+            Some(KeyboardInput::Char(c)) => typed.push(c),
+            Some(KeyboardInput::Backspace) => {
+                self.name.pop();
+            }
+            Some(KeyboardInput::Done) => done = true,
+            None => {}
+        }
+        for c in typed {
+            if !c.is_control() && self.name.len() < 24 {
+                self.name.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.name.pop();
+        }
+        // B deletes on the keyboard, so Back cancels instead
+        if is_key_pressed(KeyCode::Escape) || app.pad.pressed(PadButton::Select) {
+            return Transition::Pop;
+        }
+        if done {
+            let name = self.name.trim().to_string();
+            let saved = app
+                .game
+                .to_save()
+                .map_err(|e| e.to_string())
+                .and_then(|mut save| {
+                    save.name = name;
+                    app.storage
+                        .save_game(self.slot, &save)
+                        .map_err(|e| e.to_string())
+                });
+            // Stay in the picker on failure so the run isn't lost
+            return match saved {
+                Ok(()) => Transition::Reset(Box::new(MenuScene::default())),
+                Err(e) => {
+                    app.notify(format!("Save failed: {}", e));
+                    Transition::Pop
+                }
+            };
+        }
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        let hint = if app.pad.connected() {
+            "Start: Save  Back: Cancel"
+        } else {
+            "ENTER: Save  ESC: Cancel"
+        };
+        draw_prompt(&ui, &format!("Name: {}_", self.name), hint);
+        if app.pad.connected() {
+            self.keyboard.draw(&ui, 110.);
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
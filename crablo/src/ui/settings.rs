@@ -0,0 +1,172 @@
+use macroquad::prelude::*;
+
+use super::controls::ControlsScene;
+use super::layout::Anchor;
+use super::{draw_dim, App, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::core::settings::{Palette, Settings, MAX_UI_SCALE, MIN_UI_SCALE};
+
+/// One row of the settings screen.
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    MasterVolume,
+    SfxVolume,
+    Fullscreen,
+    Vsync,
+    UiScale,
+    ScreenShake,
+    Palette,
+    Controls,
+}
+
+const ROWS: [Row; 8] = [
+    Row::MasterVolume,
+    Row::SfxVolume,
+    Row::Fullscreen,
+    Row::Vsync,
+    Row::UiScale,
+    Row::ScreenShake,
+    Row::Palette,
+    Row::Controls,
+];
+
+impl Row {
+    fn label(self) -> &'static str {
+        match self {
+            Row::MasterVolume => "Master volume",
+            Row::SfxVolume => "Effects volume",
+            Row::Fullscreen => "Fullscreen",
+            Row::Vsync => "Vsync",
+            Row::UiScale => "UI scale",
+            Row::ScreenShake => "Screen shake",
+            Row::Palette => "Colours",
+            Row::Controls => "Controls",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" }.to_string();
+        let percent = |v: f32| format!("{}%", (v * 100.).round());
+        match self {
+            Row::MasterVolume => percent(settings.master_volume),
+            Row::SfxVolume => percent(settings.sfx_volume),
+            Row::Fullscreen => on_off(settings.fullscreen),
+            Row::Vsync => on_off(settings.vsync),
+            Row::UiScale => format!("{:.1}x", settings.ui_scale),
+            Row::ScreenShake => percent(settings.screen_shake),
+            Row::Palette => settings.palette.name().to_string(),
+            Row::Controls => "Rebind".to_string(),
+        }
+    }
+
+    /// Step the setting one notch in `dir` (-1 or 1); toggles ignore the
+    /// direction.
+    fn adjust(self, settings: &mut Settings, dir: f32) {
+        let step = |v: &mut f32, by: f32, min: f32, max: f32| {
+            // Round so repeated steps land on clean values
+            *v = ((*v + by * dir) / by).round() * by;
+            *v = v.clamp(min, max);
+        };
+        match self {
+            Row::MasterVolume => step(&mut settings.master_volume, 0.1, 0., 1.),
+            Row::SfxVolume => step(&mut settings.sfx_volume, 0.1, 0., 1.),
+            Row::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Row::Vsync => settings.vsync = !settings.vsync,
+            Row::UiScale => step(&mut settings.ui_scale, 0.1, MIN_UI_SCALE, MAX_UI_SCALE),
+            Row::ScreenShake => step(&mut settings.screen_shake, 0.25, 0., 1.),
+            Row::Palette => {
+                let count = Palette::ALL.len();
+                let i = Palette::ALL
+                    .iter()
+                    .position(|p| *p == settings.palette)
+                    .unwrap_or(0);
+                let next = if dir < 0. { i + count - 1 } else { i + 1 };
+                settings.palette = Palette::ALL[next % count];
+            }
+            // Opens its own screen instead
+            Row::Controls => {}
+        }
+    }
+}
+
+/// Settings, reachable from the menu and the pause screen. Changes apply as
+/// they are made and are written to disk on the way out.
+#[derive(Default)]
+pub struct SettingsScene {
+    selected: usize,
+}
+
+impl Scene for SettingsScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        if app.back_pressed() {
+            app.save_settings();
+            return Transition::Pop;
+        }
+        if app.menu_pressed(Action::MoveUp) {
+            self.selected = (self.selected + ROWS.len() - 1) % ROWS.len();
+        }
+        if app.menu_pressed(Action::MoveDown) {
+            self.selected = (self.selected + 1) % ROWS.len();
+        }
+        if ROWS[self.selected] == Row::Controls && app.menu_pressed(Action::Confirm) {
+            return Transition::Push(Box::new(ControlsScene::default()));
+        }
+
+        let dir = if app.menu_pressed(Action::MoveLeft) {
+            -1.
+        } else if app.menu_pressed(Action::MoveRight) || app.menu_pressed(Action::Confirm) {
+            1.
+        } else {
+            return Transition::None;
+        };
+        let mut settings = app.settings.clone();
+        ROWS[self.selected].adjust(&mut settings, dir);
+        app.change_settings(settings);
+        Transition::None
+    }
+
+    fn draw(&self, app: &App) {
+        let ui = app.layout();
+        draw_dim(Color::new(0.1, 0.1, 0.2, 1.));
+        ui.text("SETTINGS", Anchor::Top, vec2(0., 40.), 50., GOLD);
+
+        // Labels and values in two columns either side of the middle
+        let label_x = ui.place(Anchor::Top, Vec2::ZERO, vec2(-200., 0.)).x;
+        let value_x = ui.place(Anchor::Top, Vec2::ZERO, vec2(60., 0.)).x;
+        for (i, row) in ROWS.iter().enumerate() {
+            let y = ui.y(Anchor::Top, 120. + i as f32 * 40.);
+            let selected = i == self.selected;
+            let marker = if selected { "> " } else { "  " };
+            let color = if selected { YELLOW } else { WHITE };
+            ui.text_at(
+                &format!("{}{}", marker, row.label()),
+                vec2(label_x, y),
+                26.,
+                color,
+            );
+            ui.text_at(
+                &format!("< {} >", row.value(&app.settings)),
+                vec2(value_x, y),
+                26.,
+                color,
+            );
+        }
+
+        if ROWS[self.selected] == Row::Vsync {
+            ui.text(
+                "Vsync changes apply after a restart",
+                Anchor::Bottom,
+                vec2(0., -30.),
+                20.,
+                ORANGE,
+            );
+        }
+        ui.text(
+            "UP/DOWN select, LEFT/RIGHT change, ESC to save and go back",
+            Anchor::Bottom,
+            Vec2::ZERO,
+            20.,
+            GRAY,
+        );
+    }
+}
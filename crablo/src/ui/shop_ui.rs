@@ -1,50 +1,102 @@
-use crate::world::entities::ShopItem;
 use macroquad::prelude::*;
 
-pub fn draw_shop(items: &[ShopItem], gold: i32) {
-    // Dark overlay
-    draw_rectangle(
-        0.,
-        0.,
-        screen_width(),
-        screen_height(),
-        Color::new(0., 0., 0., 0.8),
-    );
+use super::layout::Anchor;
+use super::play::advance;
+use super::{draw_dim, App, Scene, Transition};
+use crate::core::bindings::Action;
+use crate::core::command::PlayerCommand;
+use crate::systems::gamepad::PadButton;
+use crate::world::entities::ShopItem;
+
+/// The shop between floors. The run keeps stepping underneath so purchases
+/// go through the simulation and end up in the replay. Keys buy by slot;
+/// a gamepad moves a cursor down the list instead.
+#[derive(Default)]
+pub struct ShopScene {
+    selected: usize,
+}
+
+impl Scene for ShopScene {
+    fn update(&mut self, app: &mut App) -> Transition {
+        let count = app.game.shop_items.len().max(1);
+        if let Some((_, dy)) = app.pad.movement() {
+            self.selected = (self.selected as i32 + dy).rem_euclid(count as i32) as usize;
+        }
+        if app.pad.pressed(PadButton::South) {
+            app.clock.queue(PlayerCommand::ShopBuy(self.selected));
+        }
+        if app.pad.pressed(PadButton::Start) || app.pad.pressed(PadButton::East) {
+            app.clock.queue(PlayerCommand::ShopLeave);
+        }
+
+        let transition = advance(app);
+        app.game
+            .update_camera(get_frame_time(), (screen_width(), screen_height()));
+        match transition {
+            Transition::None if !app.game.in_shop => Transition::Pop,
+            transition => transition,
+        }
+    }
 
-    draw_text("SHOP", screen_width() / 2. - 50., 80., 50., GOLD);
-    draw_text(
+    fn draw(&self, app: &App) {
+        let cursor = app.pad.connected().then_some(self.selected);
+        draw_shop(app, &app.game.shop_items, app.game.player.gold, cursor);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+fn draw_shop(app: &App, items: &[ShopItem], gold: i32, cursor: Option<usize>) {
+    let ui = app.layout();
+    let bindings = &app.settings.bindings;
+    draw_dim(Color::new(0., 0., 0., 0.8));
+    ui.text("SHOP", Anchor::Top, vec2(0., 40.), 50., GOLD);
+    ui.text(
         &format!("Gold: {}", gold),
-        screen_width() / 2. - 60.,
-        120.,
+        Anchor::Top,
+        vec2(0., 95.),
         24.,
         GOLD,
     );
 
-    for (i, item) in items.iter().enumerate() {
-        let y = 170. + (i as f32 * 50.);
-        let color = if item.purchased {
+    let lines: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let status = if item.purchased { " [SOLD]" } else { "" };
+            let key = Action::SHOP_SLOTS
+                .get(i)
+                .map_or("-".to_string(), |slot| bindings.primary_label(*slot));
+            format!("[{}] {} - {} gold{}", key, item.name, item.cost, status)
+        })
+        .collect();
+    let x = ui.block_x(lines.iter().map(|l| l.as_str()), 24., Anchor::Top, 0.);
+    for (i, (item, line)) in items.iter().zip(&lines).enumerate() {
+        let color = if cursor == Some(i) {
+            YELLOW
+        } else if item.purchased {
             DARKGRAY
         } else if gold >= item.cost {
             WHITE
         } else {
             RED
         };
-
-        let status = if item.purchased { " [SOLD]" } else { "" };
-        draw_text(
-            &format!("{}. {} - {} gold{}", i + 1, item.name, item.cost, status),
-            screen_width() / 2. - 150.,
-            y,
-            24.,
-            color,
-        );
+        let y = ui.y(Anchor::Top, 150. + i as f32 * 50.);
+        ui.text_at(line, vec2(x, y), 24., color);
+        if cursor == Some(i) {
+            ui.text_at(">", vec2(x - ui.px(24.), y), 24., YELLOW);
+        }
     }
 
-    draw_text(
-        "Press 1-4 to buy, ENTER to continue",
-        screen_width() / 2. - 160.,
-        screen_height() - 50.,
-        20.,
-        GRAY,
-    );
+    let hint = if cursor.is_some() {
+        "UP/DOWN select, A to buy, Start to continue".to_string()
+    } else {
+        format!(
+            "Press an item's key to buy, {} to continue",
+            bindings.primary_label(Action::Confirm)
+        )
+    };
+    ui.text(&hint, Anchor::Bottom, vec2(0., -20.), 20., GRAY);
 }
@@ -0,0 +1,148 @@
+//! Data-driven monster archetypes.
+//!
+//! Archetypes are read from a TOML file (see `assets/monsters.toml`) so
+//! enemies can be added or tuned without touching Rust. The embedded file is
+//! used whenever no override is installed.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::rng::Rng;
+
+const DEFAULT_MONSTERS: &str = include_str!("../../assets/monsters.toml");
+
+static BESTIARY: OnceLock<Bestiary> = OnceLock::new();
+
+/// Weight of an archetype for a range of floors.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpawnRule {
+    pub min_floor: i32,
+    #[serde(default)]
+    pub max_floor: Option<i32>,
+    pub weight: u32,
+}
+
+impl SpawnRule {
+    fn applies_to(&self, floor: i32) -> bool {
+        floor >= self.min_floor && self.max_floor.is_none_or(|max| floor <= max)
+    }
+}
+
+/// A monster archetype.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MonsterDef {
+    pub id: String,
+    pub name: String,
+    pub hp: i32,
+    pub damage: i32,
+    pub move_cooldown: f32,
+    pub xp: i32,
+    pub drop_chance: f32,
+    pub color: [u8; 3],
+    pub scale: f32,
+    #[serde(default)]
+    pub boss: bool,
+    #[serde(default)]
+    pub spawn: Vec<SpawnRule>,
+}
+
+/// The full roster of monster archetypes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bestiary {
+    #[serde(rename = "monster")]
+    pub monsters: Vec<MonsterDef>,
+}
+
+impl Bestiary {
+    /// Parse and validate a roster from TOML.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let bestiary: Bestiary = toml::from_str(text).map_err(|e| e.to_string())?;
+        if !bestiary.monsters.iter().any(|m| !m.boss) {
+            return Err("at least one non-boss monster is required".to_string());
+        }
+        Ok(bestiary)
+    }
+
+    /// Override file location - `monsters.toml` next to the executable.
+    pub fn default_path() -> PathBuf {
+        if let Ok(exe_path) = std::env::current_exe() {
+            if let Some(exe_dir) = exe_path.parent() {
+                return exe_dir.join("monsters.toml");
+            }
+        }
+        PathBuf::from("monsters.toml")
+    }
+
+    /// Load a roster from `path`, or the embedded defaults when there's no
+    /// file. A file that can't be read or parsed is an error so the player
+    /// learns why their monsters didn't show up.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// Stable hash of the roster, so a replay can tell whether it was
+    /// recorded against the same monsters.
+    pub fn fingerprint(&self) -> u64 {
+        let json = serde_json::to_string(self).expect("rosters always serialize");
+        // FNV-1a, which unlike `DefaultHasher` is the same on every build
+        json.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// The archetype spawned on boss floors, if any.
+    pub fn boss(&self) -> Option<&MonsterDef> {
+        self.monsters.iter().find(|m| m.boss)
+    }
+
+    /// Pick a regular archetype for `floor` using the spawn weights.
+    pub fn roll(&self, floor: i32, rng: &mut Rng) -> &MonsterDef {
+        let weight_of = |m: &MonsterDef| -> u32 {
+            m.spawn
+                .iter()
+                .filter(|rule| rule.applies_to(floor))
+                .map(|rule| rule.weight)
+                .sum()
+        };
+        let candidates = || self.monsters.iter().filter(|m| !m.boss);
+
+        let total: u32 = candidates().map(weight_of).sum();
+        if total == 0 {
+            // Nothing is weighted for this floor; fall back to the first entry
+            return candidates().next().expect("validated on load");
+        }
+
+        let mut roll = rng.gen_range(0, total);
+        for def in candidates() {
+            let weight = weight_of(def);
+            if roll < weight {
+                return def;
+            }
+            roll -= weight;
+        }
+        unreachable!("roll is below the total weight")
+    }
+}
+
+impl Default for Bestiary {
+    fn default() -> Self {
+        Self::from_toml(DEFAULT_MONSTERS).expect("embedded monsters.toml is valid")
+    }
+}
+
+/// Install the roster used for the rest of the process. Returns `false` if
+/// one was already in use.
+pub fn install(bestiary: Bestiary) -> bool {
+    BESTIARY.set(bestiary).is_ok()
+}
+
+/// The active roster, defaulting to the embedded archetypes.
+pub fn bestiary() -> &'static Bestiary {
+    BESTIARY.get_or_init(Bestiary::default)
+}
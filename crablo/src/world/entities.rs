@@ -1,70 +1,73 @@
-use crate::core::constants::MONSTER_HP;
-
-#[derive(Clone, Copy, PartialEq)]
-pub enum MonsterType {
-    Normal, // Standard enemy
-    Fast,   // Moves quickly, low HP
-    Tank,   // Slow but high HP and damage
-    Boss,   // Very strong, appears every 5 floors
-}
+use crate::core::traits::{DamageDealer, Damageable};
+use crate::world::bestiary::MonsterDef;
+use serde::{Deserialize, Serialize};
 
+/// A monster on the current floor, built from a [`MonsterDef`] archetype.
+#[derive(Serialize, Deserialize)]
 pub struct Monster {
     pub x: usize,
     pub y: usize,
     pub hp: i32,
+    /// Health after difficulty and floor scaling.
+    pub max_hp: i32,
     pub cd: f32,
-    pub monster_type: MonsterType,
+    /// Archetype id from the bestiary.
+    pub kind: String,
+    pub name: String,
     pub damage: i32,
     pub move_cd: f32,
+    pub xp: i32,
+    pub drop_chance: f32,
+    pub color: [u8; 3],
+    pub scale: f32,
+    /// Whether this is the boss of its floor.
+    #[serde(default)]
+    pub boss: bool,
 }
 
 impl Monster {
-    pub fn new(x: usize, y: usize) -> Self {
+    pub fn from_def(def: &MonsterDef, x: usize, y: usize) -> Self {
         Monster {
             x,
             y,
-            hp: MONSTER_HP,
+            hp: def.hp,
+            max_hp: def.hp,
             cd: 0.,
-            monster_type: MonsterType::Normal,
-            damage: 5,
-            move_cd: 1.0,
+            kind: def.id.clone(),
+            name: def.name.clone(),
+            damage: def.damage,
+            move_cd: def.move_cooldown,
+            xp: def.xp,
+            drop_chance: def.drop_chance,
+            color: def.color,
+            scale: def.scale,
+            boss: def.boss,
         }
     }
+}
 
-    pub fn new_fast(x: usize, y: usize) -> Self {
-        Monster {
-            x,
-            y,
-            hp: MONSTER_HP / 2, // 15 HP
-            cd: 0.,
-            monster_type: MonsterType::Fast,
-            damage: 3,
-            move_cd: 0.5, // Moves twice as fast
-        }
+impl Damageable for Monster {
+    fn hp(&self) -> i32 {
+        self.hp
     }
 
-    pub fn new_tank(x: usize, y: usize) -> Self {
-        Monster {
-            x,
-            y,
-            hp: MONSTER_HP * 2, // 60 HP
-            cd: 0.,
-            monster_type: MonsterType::Tank,
-            damage: 10,
-            move_cd: 1.5, // Slower
-        }
+    fn max_hp(&self) -> i32 {
+        self.max_hp
     }
 
-    pub fn new_boss(x: usize, y: usize) -> Self {
-        Monster {
-            x,
-            y,
-            hp: MONSTER_HP * 5, // 150 HP
-            cd: 0.,
-            monster_type: MonsterType::Boss,
-            damage: 20,
-            move_cd: 2.0, // Very slow but deadly
-        }
+    fn take_damage(&mut self, amount: i32) -> i32 {
+        self.hp -= amount;
+        amount
+    }
+
+    fn heal(&mut self, amount: i32) {
+        self.hp = (self.hp + amount).min(self.max_hp);
+    }
+}
+
+impl DamageDealer for Monster {
+    fn damage(&self) -> i32 {
+        self.damage
     }
 }
 
@@ -75,14 +78,14 @@ pub struct DmgText {
     pub life: f32,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum EquipmentType {
     Sword,  // +5 damage
     Shield, // +5 armor
     Ring,   // +20 max HP
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ShopItem {
     pub name: String,
     pub cost: i32,
@@ -90,7 +93,7 @@ pub struct ShopItem {
     pub purchased: bool,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum ShopItemType {
     Heal,   // Full heal
     MaxHp,  // +25 max HP
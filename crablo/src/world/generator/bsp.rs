@@ -0,0 +1,82 @@
+use super::{carve_corridor, Generator, Layout};
+use crate::core::rng::Rng;
+use crate::world::map::{Map, Room, Tile};
+
+/// Smallest side of a partition. Leaves are between this and twice this.
+const MIN_LEAF: usize = 8;
+const MIN_ROOM_SIZE: usize = 4;
+
+/// Binary space partition: the floor is cut in two again and again, each
+/// leaf gets a room, and the two halves of every cut are joined by a
+/// corridor. Rooms come out evenly spread and never overlap.
+pub struct Bsp;
+
+impl Generator for Bsp {
+    fn name(&self) -> &'static str {
+        "BSP"
+    }
+
+    fn generate(&self, rng: &mut Rng, width: usize, height: usize) -> Layout {
+        let mut map = Map::new(width, height, Tile::Wall);
+        let mut rooms = vec![];
+        let inside = Room::new(1, 1, width - 2, height - 2);
+        split(rng, &mut map, inside, &mut rooms);
+        let spawn = rooms[0].center();
+        Layout { map, rooms, spawn }
+    }
+}
+
+/// Partition `area`, adding its rooms to `rooms` and connecting them.
+fn split(rng: &mut Rng, map: &mut Map, area: Room, rooms: &mut Vec<Room>) {
+    let can_cut_x = area.w >= 2 * MIN_LEAF;
+    let can_cut_y = area.h >= 2 * MIN_LEAF;
+    if !can_cut_x && !can_cut_y {
+        // A leaf: one room with at least a tile of wall around it
+        let w = rng.gen_range(MIN_ROOM_SIZE, area.w - 1);
+        let h = rng.gen_range(MIN_ROOM_SIZE, area.h - 1);
+        let x = area.x + rng.gen_range(1, area.w - w);
+        let y = area.y + rng.gen_range(1, area.h - h);
+        let room = Room::new(x, y, w, h);
+        map.carve(room.x, room.y, room.w, room.h);
+        rooms.push(room);
+        return;
+    }
+
+    // Cut across the longer side so leaves stay roughly square
+    let cut_x = match (can_cut_x, can_cut_y) {
+        (true, true) if area.w == area.h => rng.gen_range(0, 2) == 0,
+        (true, true) => area.w > area.h,
+        (cut_x, _) => cut_x,
+    };
+    let (first, second) = if cut_x {
+        let at = rng.gen_range(MIN_LEAF, area.w - MIN_LEAF + 1);
+        (
+            Room { w: at, ..area },
+            Room {
+                x: area.x + at,
+                w: area.w - at,
+                ..area
+            },
+        )
+    } else {
+        let at = rng.gen_range(MIN_LEAF, area.h - MIN_LEAF + 1);
+        (
+            Room { h: at, ..area },
+            Room {
+                y: area.y + at,
+                h: area.h - at,
+                ..area
+            },
+        )
+    };
+
+    let start = rooms.len();
+    split(rng, map, first, rooms);
+    let middle = rooms.len();
+    split(rng, map, second, rooms);
+
+    // Each half is connected already, so one corridor joins them
+    let from = rooms[rng.gen_range(start, middle)].center();
+    let to = rooms[rng.gen_range(middle, rooms.len())].center();
+    carve_corridor(map, rng, from, to);
+}
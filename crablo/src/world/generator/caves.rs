@@ -0,0 +1,88 @@
+use super::rooms::RoomsAndCorridors;
+use super::{flood, interior, keep_region, open_areas, Generator, Layout};
+use crate::core::rng::Rng;
+use crate::world::map::{Map, Tile};
+
+/// Chance each tile starts as wall.
+const INITIAL_WALLS: f32 = 0.45;
+/// Smoothing passes.
+const STEPS: usize = 5;
+/// Share of the floor the largest cave must cover to be kept.
+const MIN_OPEN: f32 = 0.35;
+const ATTEMPTS: usize = 10;
+
+/// Cellular-automata caves: random noise smoothed until it forms open
+/// caverns, keeping only the largest one so everything is reachable.
+pub struct CellularCaves;
+
+impl Generator for CellularCaves {
+    fn name(&self) -> &'static str {
+        "cellular caves"
+    }
+
+    fn generate(&self, rng: &mut Rng, width: usize, height: usize) -> Layout {
+        let inside = (width - 2) * (height - 2);
+        for _ in 0..ATTEMPTS {
+            let mut map = Map::new(width, height, Tile::Wall);
+            for (x, y) in interior(width, height) {
+                if rng.next_f32() >= INITIAL_WALLS {
+                    map.set(x, y, Tile::Floor);
+                }
+            }
+            for _ in 0..STEPS {
+                map = smooth(&map);
+            }
+
+            let Some(largest) = largest_cave(&map) else {
+                continue;
+            };
+            let cave = keep_region(&mut map, largest);
+            if (cave.len() as f32) < inside as f32 * MIN_OPEN {
+                continue;
+            }
+            let spawn = cave[rng.gen_range(0, cave.len())];
+            let rooms = open_areas(rng, &cave, spawn);
+            return Layout { map, rooms, spawn };
+        }
+        // Noise that never opens up is vanishingly rare, but a floor is
+        // still needed
+        RoomsAndCorridors.generate(rng, width, height)
+    }
+}
+
+/// One smoothing pass: tiles mostly surrounded by wall become wall, tiles
+/// mostly surrounded by floor become floor. The outer wall stays.
+fn smooth(map: &Map) -> Map {
+    let mut next = map.clone();
+    for (x, y) in interior(map.width(), map.height()) {
+        let walls = map
+            .neighbours((x, y))
+            .filter(|&((nx, ny), _)| !map.is_floor(nx, ny))
+            .count();
+        if walls >= 5 {
+            next.set(x, y, Tile::Wall);
+        } else if walls <= 3 {
+            next.set(x, y, Tile::Floor);
+        }
+    }
+    next
+}
+
+/// A tile of the largest connected cave, if there is any floor at all.
+fn largest_cave(map: &Map) -> Option<(usize, usize)> {
+    let mut seen = map.same_size(false);
+    let mut best: Option<((usize, usize), usize)> = None;
+    for (x, y) in map.positions() {
+        if !map.is_floor(x, y) || seen.get(x, y) == Some(true) {
+            continue;
+        }
+        let cave = flood(map, (x, y));
+        for &(cx, cy) in &cave {
+            seen.set(cx, cy, true);
+        }
+        if best.map_or(0, |(_, size)| size) < cave.len() {
+            best = Some(((x, y), cave.len()));
+        }
+    }
+    best.map(|(start, _)| start)
+}
@@ -0,0 +1,44 @@
+use super::{open_areas, Generator, Layout};
+use crate::core::rng::Rng;
+use crate::world::map::{Map, Tile};
+
+/// Share of the floor the walk digs out.
+const COVERAGE: f32 = 0.4;
+
+const STEPS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+/// Drunkard's walk: one digger stumbles around from the middle of the
+/// floor until enough of it is open. Everything dug is on its one path, so
+/// it is all connected.
+pub struct DrunkardWalk;
+
+impl Generator for DrunkardWalk {
+    fn name(&self) -> &'static str {
+        "drunkard's walk"
+    }
+
+    fn generate(&self, rng: &mut Rng, width: usize, height: usize) -> Layout {
+        let mut map = Map::new(width, height, Tile::Wall);
+        let target = ((width - 2) * (height - 2)) as f32 * COVERAGE;
+        let spawn = (width / 2, height / 2);
+
+        let mut pos = spawn;
+        let mut dug = vec![];
+        while (dug.len() as f32) < target {
+            if !map.is_floor(pos.0, pos.1) {
+                map.set(pos.0, pos.1, Tile::Floor);
+                dug.push(pos);
+            }
+            let step = STEPS[rng.gen_range(0, STEPS.len())];
+            // Stay off the outer wall
+            if let Some(next) = map.step(pos, step) {
+                if next.0 > 0 && next.1 > 0 && next.0 < width - 1 && next.1 < height - 1 {
+                    pos = next;
+                }
+            }
+        }
+
+        let rooms = open_areas(rng, &dug, spawn);
+        Layout { map, rooms, spawn }
+    }
+}
@@ -0,0 +1,415 @@
+//! Floor generation.
+//!
+//! A [`Generator`] lays out one floor: a [`Map`], the rooms in it and where
+//! the player starts. Which generator builds a floor depends on its
+//! [`Biome`]. Every generator must leave all floor tiles reachable from the
+//! spawn; debug builds check this on every floor.
+//!
+//! The stairs down go on the tile farthest from the spawn. Each room then
+//! gets a [`RoomRole`], and monsters, loot and traps are placed according to
+//! it, whichever generator built the floor.
+//!
+//! - [`rooms`] - Rooms joined by L-shaped corridors
+//! - [`bsp`] - Binary space partition into evenly spread rooms
+//! - [`caves`] - Cellular-automata caves
+//! - [`drunkard`] - Drunkard's walk tunnels
+
+pub mod bsp;
+pub mod caves;
+pub mod drunkard;
+pub mod rooms;
+
+use crate::core::constants::{BOSS_FLOOR_INTERVAL, SHOP_FLOOR_INTERVAL};
+use crate::core::rng::Rng;
+use crate::systems::pathfinding::{dist, distances};
+use crate::world::map::{floor_size, Map, Room, RoomRole, Tile};
+
+use bsp::Bsp;
+use caves::CellularCaves;
+use drunkard::DrunkardWalk;
+use rooms::RoomsAndCorridors;
+
+/// The shape of a floor, before anything is placed in it.
+pub struct Layout {
+    pub map: Map,
+    /// Rooms, or open areas on floors without real rooms. The first one
+    /// holds the spawn.
+    pub rooms: Vec<Room>,
+    /// Where the player starts.
+    pub spawn: (usize, usize),
+}
+
+/// A generated floor, furnished room by room.
+pub struct FloorPlan {
+    pub map: Map,
+    /// Rooms with their roles. The first one is the start room.
+    pub rooms: Vec<Room>,
+    pub spawn: (usize, usize),
+    pub gold: Vec<(usize, usize)>,
+    pub potions: Vec<(usize, usize)>,
+    /// Monster positions; archetypes are rolled by the caller from the
+    /// bestiary. On boss floors the first one is in the boss room.
+    pub monsters: Vec<(usize, usize)>,
+    pub spikes: Vec<(usize, usize)>,
+    pub poison: Vec<(usize, usize)>,
+}
+
+impl FloorPlan {
+    /// Hand out room roles for `floor` and place everything by them.
+    fn furnish(rng: &mut Rng, layout: Layout, floor: i32) -> Self {
+        let Layout {
+            map,
+            mut rooms,
+            spawn,
+        } = layout;
+        assign_roles(rng, &map, &mut rooms, spawn, floor);
+
+        let mut plan = FloorPlan {
+            map,
+            rooms: rooms.clone(),
+            spawn,
+            gold: vec![],
+            potions: vec![],
+            monsters: vec![],
+            spikes: vec![],
+            poison: vec![],
+        };
+        // The boss room goes first so the boss is the first monster
+        let mut order: Vec<&Room> = rooms.iter().collect();
+        order.sort_by_key(|room| room.role != RoomRole::Boss);
+        for room in order {
+            plan.fill(rng, room);
+        }
+
+        // A floor with nothing to fight would end the moment it starts
+        if plan.monsters.is_empty() {
+            for room in rooms.iter().skip(1).rev() {
+                let found = plan.pick(rng, room, 1);
+                if !found.is_empty() {
+                    plan.monsters.extend(found);
+                    break;
+                }
+            }
+        }
+
+        plan
+    }
+
+    /// Place what `room`'s role calls for.
+    fn fill(&mut self, rng: &mut Rng, room: &Room) {
+        match room.role {
+            RoomRole::Start | RoomRole::Shop => {}
+            RoomRole::Chamber => {
+                let count = rng.gen_range(1, 3);
+                let monsters = self.pick(rng, room, count);
+                self.monsters.extend(monsters);
+                if rng.gen_range(0, 2) == 0 {
+                    let gold = self.pick(rng, room, 1);
+                    self.gold.extend(gold);
+                }
+                if rng.gen_range(0, 4) == 0 {
+                    let potions = self.pick(rng, room, 1);
+                    self.potions.extend(potions);
+                }
+            }
+            RoomRole::Treasure => {
+                let count = rng.gen_range(2, 4);
+                let gold = self.pick(rng, room, count);
+                self.gold.extend(gold);
+                let spikes = self.pick(rng, room, 2);
+                self.spikes.extend(spikes);
+                let guard = self.pick(rng, room, 1);
+                self.monsters.extend(guard);
+            }
+            RoomRole::MonsterDen => {
+                let count = rng.gen_range(3, 5);
+                let monsters = self.pick(rng, room, count);
+                self.monsters.extend(monsters);
+                let poison = self.pick(rng, room, 1);
+                self.poison.extend(poison);
+            }
+            RoomRole::Shrine => {
+                let potions = self.pick(rng, room, 2);
+                self.potions.extend(potions);
+            }
+            RoomRole::Boss => {
+                // The boss holds the middle of the room when it can
+                let center = room.center();
+                if self.is_free(center) {
+                    self.monsters.push(center);
+                }
+                let count = rng.gen_range(1, 3);
+                let guards = self.pick(rng, room, count);
+                self.monsters.extend(guards);
+            }
+        }
+    }
+
+    /// Up to `count` random free tiles of `room`.
+    fn pick(&self, rng: &mut Rng, room: &Room, count: usize) -> Vec<(usize, usize)> {
+        let mut free: Vec<_> = room.tiles().filter(|&pos| self.is_free(pos)).collect();
+        let mut picked = vec![];
+        while picked.len() < count && !free.is_empty() {
+            picked.push(free.swap_remove(rng.gen_range(0, free.len())));
+        }
+        picked
+    }
+
+    /// Whether `pos` is an empty floor tile away from the spawn and the
+    /// shop stall.
+    fn is_free(&self, pos: (usize, usize)) -> bool {
+        self.map.get(pos.0, pos.1) == Some(Tile::Floor)
+            && pos != self.spawn
+            && !self
+                .rooms
+                .iter()
+                .any(|room| room.role == RoomRole::Shop && room.center() == pos)
+            && ![
+                &self.gold,
+                &self.potions,
+                &self.monsters,
+                &self.spikes,
+                &self.poison,
+            ]
+            .iter()
+            .any(|placed| placed.contains(&pos))
+    }
+}
+
+/// Give the first room the start role, the farthest one from the spawn the
+/// boss on boss floors, a random one the shop on shop floors, and roll the
+/// rest.
+fn assign_roles(
+    rng: &mut Rng,
+    map: &Map,
+    rooms: &mut Vec<Room>,
+    spawn: (usize, usize),
+    floor: i32,
+) {
+    rooms[0].role = RoomRole::Start;
+    let mut open: Vec<usize> = (1..rooms.len()).collect();
+
+    if floor % BOSS_FLOOR_INTERVAL == 0 {
+        if let Some(i) = (0..open.len()).max_by_key(|&i| dist(rooms[open[i]].center(), spawn)) {
+            rooms[open.remove(i)].role = RoomRole::Boss;
+        }
+    }
+    if floor % SHOP_FLOOR_INTERVAL == 0 {
+        // Keep at least one room to fight in. The stall stands in the
+        // middle of the shop, so that has to be floor the player can step
+        // onto
+        let stalls: Vec<usize> = (0..open.len())
+            .filter(|&i| {
+                let (x, y) = rooms[open[i]].center();
+                map.get(x, y) == Some(Tile::Floor) && (x, y) != spawn
+            })
+            .collect();
+        if open.len() >= 2 && !stalls.is_empty() {
+            let i = stalls[rng.gen_range(0, stalls.len())];
+            rooms[open.remove(i)].role = RoomRole::Shop;
+        } else {
+            // No room to spare: the stall stands on its own, away from the
+            // start and the boss where it can
+            let kept_clear: Vec<&Room> = rooms
+                .iter()
+                .filter(|room| matches!(room.role, RoomRole::Start | RoomRole::Boss))
+                .collect();
+            let floor_tiles: Vec<_> = map
+                .positions()
+                .filter(|&(x, y)| map.get(x, y) == Some(Tile::Floor) && (x, y) != spawn)
+                .collect();
+            let away: Vec<_> = floor_tiles
+                .iter()
+                .copied()
+                .filter(|&pos| !kept_clear.iter().any(|room| room.tiles().any(|t| t == pos)))
+                .collect();
+            let spots = if away.is_empty() { floor_tiles } else { away };
+            if let Some(&(x, y)) = spots.get(rng.gen_range(0, spots.len())) {
+                let mut stall = Room::new(x, y, 1, 1);
+                stall.role = RoomRole::Shop;
+                rooms.push(stall);
+            }
+        }
+    }
+
+    for i in open {
+        rooms[i].role = match rng.gen_range(0, 10) {
+            0 | 1 => RoomRole::Treasure,
+            2 | 3 => RoomRole::MonsterDen,
+            4 => RoomRole::Shrine,
+            _ => RoomRole::Chamber,
+        };
+    }
+}
+
+/// A floor layout algorithm.
+pub trait Generator {
+    fn name(&self) -> &'static str;
+
+    /// Lay out a `width` x `height` floor with a wall all the way around.
+    /// Every floor tile must be reachable from the spawn.
+    fn generate(&self, rng: &mut Rng, width: usize, height: usize) -> Layout;
+}
+
+/// The look of a stretch of floors. Each one is built by its own generator.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Biome {
+    Halls,
+    Crypts,
+    Caves,
+    Tunnels,
+}
+
+impl Biome {
+    /// Biomes in the order the dungeon goes through them.
+    pub const ALL: [Biome; 4] = [Biome::Halls, Biome::Crypts, Biome::Caves, Biome::Tunnels];
+
+    /// Biome of `floor`. Each lasts up to and including a boss floor, then
+    /// the next one starts, wrapping around past the last.
+    pub fn for_floor(floor: i32) -> Biome {
+        let stretch = (floor.max(1) - 1) / BOSS_FLOOR_INTERVAL;
+        Biome::ALL[stretch as usize % Biome::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Halls => "Halls",
+            Biome::Crypts => "Crypts",
+            Biome::Caves => "Caves",
+            Biome::Tunnels => "Tunnels",
+        }
+    }
+
+    pub fn generator(self) -> &'static dyn Generator {
+        match self {
+            Biome::Halls => &RoomsAndCorridors,
+            Biome::Crypts => &Bsp,
+            Biome::Caves => &CellularCaves,
+            Biome::Tunnels => &DrunkardWalk,
+        }
+    }
+}
+
+/// Generate `floor` of a run with the generator of its biome and furnish
+/// its rooms.
+pub fn generate_floor(rng: &mut Rng, floor: i32) -> FloorPlan {
+    let (width, height) = floor_size(floor);
+    let generator = Biome::for_floor(floor).generator();
+    let mut layout = generator.generate(rng, width, height);
+    debug_assert!(
+        is_connected(&layout.map, layout.spawn),
+        "{} left part of floor {} unreachable",
+        generator.name(),
+        floor
+    );
+    place_stairs(&mut layout.map, layout.spawn);
+    FloorPlan::furnish(rng, layout, floor)
+}
+
+/// Put the stairs down on the floor tile the longest walk from `from`.
+fn place_stairs(map: &mut Map, from: (usize, usize)) -> (usize, usize) {
+    let distance = distances(map, from);
+    let stairs = map
+        .positions()
+        .filter(|&(x, y)| map.is_floor(x, y))
+        .max_by_key(|&(x, y)| distance.get(x, y).flatten())
+        .unwrap_or(from);
+    map.set(stairs.0, stairs.1, Tile::StairsDown);
+    stairs
+}
+
+/// Whether every floor tile can be walked to from `start`.
+pub fn is_connected(map: &Map, start: (usize, usize)) -> bool {
+    let floor_tiles = map.positions().filter(|&(x, y)| map.is_floor(x, y)).count();
+    map.is_floor(start.0, start.1) && flood(map, start).len() == floor_tiles
+}
+
+/// Floor tiles reachable from `start`. Only cardinal steps are followed:
+/// diagonal moves can't cut wall corners, so they never reach anything
+/// cardinal steps can't.
+fn flood(map: &Map, start: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut seen = map.same_size(false);
+    let mut stack = vec![start];
+    let mut region = vec![];
+    seen.set(start.0, start.1, true);
+    while let Some(pos) = stack.pop() {
+        region.push(pos);
+        for ((nx, ny), (dx, dy)) in map.neighbours(pos) {
+            if (dx == 0 || dy == 0) && map.is_floor(nx, ny) && seen.get(nx, ny) == Some(false) {
+                seen.set(nx, ny, true);
+                stack.push((nx, ny));
+            }
+        }
+    }
+    region
+}
+
+/// Join `from` and `to` with a two-wide L-shaped corridor, turning at a
+/// random corner.
+fn carve_corridor(map: &mut Map, rng: &mut Rng, from: (usize, usize), to: (usize, usize)) {
+    let ((x1, y1), (x2, y2)) = (from, to);
+    if rng.gen_range(0, 2) == 0 {
+        carve_h_corridor(map, x1, x2, y1);
+        carve_v_corridor(map, y1, y2, x2);
+    } else {
+        carve_v_corridor(map, y1, y2, x1);
+        carve_h_corridor(map, x1, x2, y2);
+    }
+}
+
+fn carve_h_corridor(map: &mut Map, x1: usize, x2: usize, y: usize) {
+    let (start, end) = if x1 < x2 { (x1, x2) } else { (x2, x1) };
+    if y > 0 && y < map.height() - 1 {
+        map.carve(start, y, end - start + 1, 1);
+        // Make corridor 2 tiles wide
+        if y > 1 {
+            map.carve(start, y - 1, end - start + 1, 1);
+        }
+    }
+}
+
+fn carve_v_corridor(map: &mut Map, y1: usize, y2: usize, x: usize) {
+    let (start, end) = if y1 < y2 { (y1, y2) } else { (y2, y1) };
+    if x > 0 && x < map.width() - 1 {
+        map.carve(x, start, 1, end - start + 1);
+        // Make corridor 2 tiles wide
+        if x > 1 {
+            map.carve(x - 1, start, 1, end - start + 1);
+        }
+    }
+}
+
+/// Mark out open areas of a floor without real rooms: one around the spawn,
+/// then one around a random tile of `open` for every 40 or so tiles.
+fn open_areas(rng: &mut Rng, open: &[(usize, usize)], spawn: (usize, usize)) -> Vec<Room> {
+    let area = |(x, y): (usize, usize)| {
+        Room::new(x.saturating_sub(2).max(1), y.saturating_sub(2).max(1), 5, 5)
+    };
+    let count = (open.len() / 40).clamp(2, 12);
+    let mut rooms = vec![area(spawn)];
+    for _ in 1..count {
+        rooms.push(area(open[rng.gen_range(0, open.len())]));
+    }
+    rooms
+}
+
+/// Tiles inside the outer wall of a `width` x `height` map.
+fn interior(width: usize, height: usize) -> impl Iterator<Item = (usize, usize)> {
+    (1..height - 1).flat_map(move |y| (1..width - 1).map(move |x| (x, y)))
+}
+
+/// Wall off every floor tile not reachable from `start` and return the
+/// ones that are.
+fn keep_region(map: &mut Map, start: (usize, usize)) -> Vec<(usize, usize)> {
+    let region = flood(map, start);
+    let mut reachable = map.same_size(false);
+    for &(x, y) in &region {
+        reachable.set(x, y, true);
+    }
+    for (x, y) in map.positions() {
+        if reachable.get(x, y) == Some(false) {
+            map.set(x, y, Tile::Wall);
+        }
+    }
+    region
+}
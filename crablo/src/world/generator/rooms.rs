@@ -0,0 +1,51 @@
+use super::{carve_corridor, Generator, Layout};
+use crate::core::constants::MAP_MIN_SIZE;
+use crate::core::rng::Rng;
+use crate::world::map::{Map, Room, Tile};
+
+const MIN_ROOM_SIZE: usize = 4;
+const MAX_ROOM_SIZE: usize = 7;
+
+/// Rooms dropped at random where they fit, each joined to the one placed
+/// before it by an L-shaped corridor. The chain of corridors keeps every
+/// room reachable.
+pub struct RoomsAndCorridors;
+
+impl Generator for RoomsAndCorridors {
+    fn name(&self) -> &'static str {
+        "rooms and corridors"
+    }
+
+    fn generate(&self, rng: &mut Rng, width: usize, height: usize) -> Layout {
+        // Bigger floors get more tries, so they aren't emptier
+        let room_attempts = 8 * (width * height).div_ceil(MAP_MIN_SIZE * MAP_MIN_SIZE);
+
+        loop {
+            let mut map = Map::new(width, height, Tile::Wall);
+            let mut rooms: Vec<Room> = vec![];
+
+            for _ in 0..room_attempts {
+                let w = rng.gen_range(MIN_ROOM_SIZE, MAX_ROOM_SIZE);
+                let h = rng.gen_range(MIN_ROOM_SIZE, MAX_ROOM_SIZE);
+                let x = rng.gen_range(1, width - w - 1);
+                let y = rng.gen_range(1, height - h - 1);
+                let new_room = Room::new(x, y, w, h);
+
+                if rooms.iter().any(|room| new_room.intersects(room)) {
+                    continue;
+                }
+                map.carve(x, y, w, h);
+                if let Some(prev) = rooms.last() {
+                    carve_corridor(&mut map, rng, prev.center(), new_room.center());
+                }
+                rooms.push(new_room);
+            }
+
+            // Try again until at least 2 rooms fit
+            if rooms.len() >= 2 {
+                let spawn = rooms[0].center();
+                return Layout { map, rooms, spawn };
+            }
+        }
+    }
+}
@@ -1,170 +1,250 @@
-use crate::core::constants::MAP_SIZE;
-use macroquad::rand::gen_range;
+//! Dungeon maps and floor sizes.
+//!
+//! A [`Map`] is a grid sized when the floor is generated. Every access is
+//! bounds-checked, so callers never index past the edge of a floor. The
+//! layouts themselves come from [`crate::world::generator`].
 
-#[derive(Copy, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use crate::core::constants::{
+    BOSS_ARENA_EXTRA_WIDTH, BOSS_FLOOR_INTERVAL, MAP_GROWTH_FLOORS, MAP_MAX_SIZE, MAP_MIN_SIZE,
+};
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Tile {
     Wall,
     Floor,
+    /// Leads to the next floor. Walked on like floor.
+    StairsDown,
 }
 
-#[derive(Clone, Copy)]
-struct Room {
-    x: usize,
-    y: usize,
-    w: usize,
-    h: usize,
+/// The eight steps to neighbouring cells, cardinals first.
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+
+/// A `width` x `height` grid of cells, tiles by default. Also used for
+/// per-tile state such as which tiles have been explored.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "MapData<T>")]
+pub struct Map<T = Tile> {
+    width: usize,
+    height: usize,
+    /// Row-major.
+    cells: Vec<T>,
 }
 
-impl Room {
-    fn center(&self) -> (usize, usize) {
-        (self.x + self.w / 2, self.y + self.h / 2)
-    }
-
-    fn intersects(&self, other: &Room) -> bool {
-        // Allow rooms to overlap/merge for more open space
-        self.x < other.x + other.w
-            && self.x + self.w > other.x
-            && self.y < other.y + other.h
-            && self.y + self.h > other.y
+impl<T> Map<T> {
+    /// A map over existing row-major `cells`, which must fill it exactly.
+    fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Result<Self, String> {
+        if width.checked_mul(height) != Some(cells.len()) {
+            return Err(format!(
+                "{} cells don't fill a {}x{} map",
+                cells.len(),
+                width,
+                height
+            ));
+        }
+        Ok(Map {
+            width,
+            height,
+            cells,
+        })
     }
 }
 
-pub fn create_map() -> (
-    [[Tile; MAP_SIZE]; MAP_SIZE],
-    Vec<(usize, usize)>,
-    Vec<(usize, usize, u8)>,
-) {
-    let mut map = [[Tile::Wall; MAP_SIZE]; MAP_SIZE];
-    let mut rooms: Vec<Room> = vec![];
-
-    let room_attempts = 8;
-    let min_room_size = 4;
-    let max_room_size = 7;
-
-    // Generate rooms
-    for _ in 0..room_attempts {
-        let w = gen_range(min_room_size, max_room_size);
-        let h = gen_range(min_room_size, max_room_size);
-        let x = gen_range(1, MAP_SIZE - w - 1);
-        let y = gen_range(1, MAP_SIZE - h - 1);
-
-        let new_room = Room { x, y, w, h };
-
-        let mut ok = true;
-        for room in &rooms {
-            if new_room.intersects(room) {
-                ok = false;
-                break;
-            }
+impl<T: Copy> Map<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Map {
+            width,
+            height,
+            cells: vec![fill; width * height],
         }
+    }
 
-        if ok {
-            // Carve out the room
-            for ry in new_room.y..new_room.y + new_room.h {
-                for rx in new_room.x..new_room.x + new_room.w {
-                    map[ry][rx] = Tile::Floor;
-                }
-            }
+    /// A map of the same size with every cell set to `fill`.
+    pub fn same_size<U: Copy>(&self, fill: U) -> Map<U> {
+        Map::new(self.width, self.height, fill)
+    }
 
-            // Connect to previous room with corridors
-            if !rooms.is_empty() {
-                let (new_cx, new_cy) = new_room.center();
-                let (prev_cx, prev_cy) = rooms.last().unwrap().center();
-
-                // Randomly choose horizontal-first or vertical-first
-                if gen_range(0, 2) == 0 {
-                    carve_h_corridor(&mut map, prev_cx, new_cx, prev_cy);
-                    carve_v_corridor(&mut map, prev_cy, new_cy, new_cx);
-                } else {
-                    carve_v_corridor(&mut map, prev_cy, new_cy, prev_cx);
-                    carve_h_corridor(&mut map, prev_cx, new_cx, new_cy);
-                }
-            }
+    pub fn width(&self) -> usize {
+        self.width
+    }
 
-            rooms.push(new_room);
-        }
+    pub fn height(&self) -> usize {
+        self.height
     }
 
-    // Ensure we have at least 2 rooms
-    if rooms.len() < 2 {
-        return create_map(); // Retry
+    /// Whether `(x, y)` lies on the map. Takes signed coordinates so
+    /// positions stepped off the top or left edge can be checked too.
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
     }
 
-    // Place gold in random rooms (not the first room where player spawns)
-    let mut gold_positions = vec![];
-    for room in rooms.iter().skip(1) {
-        if gen_range(0, 3) < 2 {
-            // 66% chance
-            let gx = gen_range(room.x + 1, room.x + room.w - 1);
-            let gy = gen_range(room.y + 1, room.y + room.h - 1);
-            gold_positions.push((gx, gy));
-        }
+    /// The cell at `(x, y)`, or `None` off the map.
+    pub fn get(&self, x: usize, y: usize) -> Option<T> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
     }
 
-    // Place monsters in rooms (not the first room)
-    // Returns (x, y, monster_type) where type: 0=normal, 1=fast, 2=tank
-    let mut monster_positions = vec![];
-    for room in rooms.iter().skip(1) {
-        let num_monsters = gen_range(1, 3);
-        for _ in 0..num_monsters {
-            let mx = gen_range(room.x, room.x + room.w);
-            let my = gen_range(room.y, room.y + room.h);
-            if !monster_positions
-                .iter()
-                .any(|(x, y, _)| *x == mx && *y == my)
-            {
-                // Randomly choose monster type: 60% normal, 25% fast, 15% tank
-                let roll = gen_range(0, 100);
-                let mtype: u8 = if roll < 60 {
-                    0
-                } else if roll < 85 {
-                    1
-                } else {
-                    2
-                };
-                monster_positions.push((mx, my, mtype));
-            }
+    /// Set the cell at `(x, y)`. Writes off the map are ignored.
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = value;
         }
     }
 
-    // Return player spawn position (center of first room)
-    (map, gold_positions, monster_positions)
+    /// The position one `(dx, dy)` step from `(x, y)`, if it is on the map.
+    pub fn step(&self, (x, y): (usize, usize), (dx, dy): (i32, i32)) -> Option<(usize, usize)> {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        self.in_bounds(nx, ny).then_some((nx as usize, ny as usize))
+    }
+
+    /// Every position on the map, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// The up to eight positions around `pos` that are on the map, with the
+    /// step that reaches each. Cardinal neighbours come first.
+    pub fn neighbours(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), (i32, i32))> + '_ {
+        NEIGHBOURS
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir).map(|next| (next, dir)))
+    }
+
+    /// Positions on the map within `radius` steps of `(x, y)` on both axes.
+    pub fn around(
+        &self,
+        (x, y): (usize, usize),
+        radius: i32,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (-radius..=radius).flat_map(move |dy| {
+            (-radius..=radius).filter_map(move |dx| self.step((x, y), (dx, dy)))
+        })
+    }
 }
 
-pub fn get_player_spawn(map: &[[Tile; MAP_SIZE]; MAP_SIZE]) -> (usize, usize) {
-    // Find first floor tile (should be in first room)
-    for y in 1..MAP_SIZE - 1 {
-        for x in 1..MAP_SIZE - 1 {
-            if map[y][x] == Tile::Floor {
-                return (x, y);
+impl Map<Tile> {
+    /// Whether `(x, y)` is on the map and walkable.
+    pub fn is_floor(&self, x: usize, y: usize) -> bool {
+        matches!(self.get(x, y), Some(Tile::Floor | Tile::StairsDown))
+    }
+
+    /// Where the stairs down are, if the floor has any.
+    #[allow(dead_code)]
+    pub fn stairs(&self) -> Option<(usize, usize)> {
+        self.positions()
+            .find(|&(x, y)| self.get(x, y) == Some(Tile::StairsDown))
+    }
+
+    /// Turn the `w` x `h` box at `(x, y)` into floor.
+    pub fn carve(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        for ty in y..y + h {
+            for tx in x..x + w {
+                self.set(tx, ty, Tile::Floor);
             }
         }
     }
-    (2, 2) // Fallback
 }
 
-fn carve_h_corridor(map: &mut [[Tile; MAP_SIZE]; MAP_SIZE], x1: usize, x2: usize, y: usize) {
-    let (start, end) = if x1 < x2 { (x1, x2) } else { (x2, x1) };
-    for x in start..=end {
-        if y > 0 && y < MAP_SIZE - 1 {
-            map[y][x] = Tile::Floor;
-            // Make corridor 2 tiles wide
-            if y > 1 {
-                map[y - 1][x] = Tile::Floor;
-            }
-        }
+/// Serialized form of a [`Map`], checked before it becomes one.
+#[derive(Deserialize)]
+struct MapData<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> TryFrom<MapData<T>> for Map<T> {
+    type Error = String;
+
+    fn try_from(data: MapData<T>) -> Result<Self, String> {
+        Map::from_cells(data.width, data.height, data.cells)
     }
 }
 
-fn carve_v_corridor(map: &mut [[Tile; MAP_SIZE]; MAP_SIZE], y1: usize, y2: usize, x: usize) {
-    let (start, end) = if y1 < y2 { (y1, y2) } else { (y2, y1) };
-    for y in start..=end {
-        if x > 0 && x < MAP_SIZE - 1 {
-            map[y][x] = Tile::Floor;
-            // Make corridor 2 tiles wide
-            if x > 1 {
-                map[y][x - 1] = Tile::Floor;
-            }
+/// Width and height of `floor`. Floors grow every few floors up to a cap,
+/// and boss floors are a wide arena instead.
+pub fn floor_size(floor: i32) -> (usize, usize) {
+    let growth = 2 * ((floor.max(1) as usize - 1) / MAP_GROWTH_FLOORS);
+    let size = (MAP_MIN_SIZE + growth).min(MAP_MAX_SIZE);
+    if floor % BOSS_FLOOR_INTERVAL == 0 {
+        (size + BOSS_ARENA_EXTRA_WIDTH, size)
+    } else {
+        (size, size)
+    }
+}
+
+/// What a room is for. Generators only lay out rooms; roles are handed out
+/// per floor and decide what gets placed in each one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum RoomRole {
+    /// Where the player arrives. Kept clear of monsters and traps.
+    Start,
+    /// An ordinary room: a monster or two and maybe some gold.
+    #[default]
+    Chamber,
+    /// A hoard of gold guarded by spike traps.
+    Treasure,
+    /// A crowd of monsters around a poison trap.
+    MonsterDen,
+    /// Healing potions and nothing hostile.
+    Shrine,
+    /// Its stall opens the shop. Only on shop floors.
+    Shop,
+    /// The boss and its guards. Only on boss floors.
+    Boss,
+}
+
+/// A rectangular area of a floor. Generators without real rooms mark out
+/// open areas with these instead.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Room {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+    #[serde(default)]
+    pub role: RoomRole,
+}
+
+impl Room {
+    pub fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Room {
+            x,
+            y,
+            w,
+            h,
+            role: RoomRole::default(),
         }
     }
+
+    pub fn center(&self) -> (usize, usize) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    /// Every tile inside the room, row by row.
+    pub fn tiles(&self) -> impl Iterator<Item = (usize, usize)> {
+        let Room { x, y, w, h, .. } = *self;
+        (y..y + h).flat_map(move |ty| (x..x + w).map(move |tx| (tx, ty)))
+    }
+
+    pub fn intersects(&self, other: &Room) -> bool {
+        // Allow rooms to overlap/merge for more open space
+        self.x < other.x + other.w
+            && self.x + self.w > other.x
+            && self.y < other.y + other.h
+            && self.y + self.h > other.y
+    }
 }
@@ -1,8 +1,12 @@
 //! World module containing game entities and map generation.
 //!
 //! This module provides:
+//! - [`bestiary`] - Data-driven monster archetypes
 //! - [`entities`] - Game entities (monsters, items, effects)
-//! - [`map`] - Procedural map generation
+//! - [`generator`] - Floor generators and biomes
+//! - [`map`] - Map grid and floor sizes
 
+pub mod bestiary;
 pub mod entities;
+pub mod generator;
 pub mod map;
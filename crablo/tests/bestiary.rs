@@ -0,0 +1,41 @@
+//! Monster rosters load from TOML, and only a missing file means defaults.
+
+use std::path::PathBuf;
+
+use crablo::world::bestiary::Bestiary;
+
+/// A path in the temp dir, removed when dropped.
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("crablo-bestiary-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        TempPath(path)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+        let _ = std::fs::remove_dir(&self.0);
+    }
+}
+
+#[test]
+fn only_a_missing_roster_falls_back_to_the_defaults() {
+    let missing = TempPath::new("missing.toml");
+    let roster = Bestiary::load(&missing.0).unwrap();
+    assert_eq!(roster.fingerprint(), Bestiary::default().fingerprint());
+
+    let broken = TempPath::new("broken.toml");
+    std::fs::write(&broken.0, "[[monster]]\nid = \"rat\"\n").unwrap();
+    let error = Bestiary::load(&broken.0).err().unwrap();
+    assert!(error.contains("broken.toml"), "{}", error);
+
+    // Files that exist but can't be read are reported too
+    let unreadable = TempPath::new("unreadable.toml");
+    std::fs::create_dir(&unreadable.0).unwrap();
+    assert!(Bestiary::load(&unreadable.0).is_err());
+}
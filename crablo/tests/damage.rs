@@ -0,0 +1,94 @@
+//! Armor counts the same way wherever a hit comes from.
+
+use crablo::core::command::PlayerCommand;
+use crablo::core::constants::{Difficulty, SIM_DT};
+use crablo::core::damage::{self, DamageModifiers, DamageSource, DamageType, Hit};
+use crablo::core::player::Player;
+use crablo::core::Game;
+
+/// A floor-one run with nobody else around and the given armor.
+fn armored(armor: i32) -> Game {
+    let mut game = Game::with_seed(Difficulty::Normal, 4);
+    game.monsters.clear();
+    game.player.armor = armor;
+    game
+}
+
+/// A walkable direction from the player's tile.
+fn open_step(game: &Game) -> ((i32, i32), (usize, usize)) {
+    [(1, 0), (-1, 0), (0, 1), (0, -1)]
+        .into_iter()
+        .find_map(|dir| {
+            let to = game.map.step((game.player.x, game.player.y), dir)?;
+            game.map.is_floor(to.0, to.1).then_some((dir, to))
+        })
+        .expect("the spawn has a free neighbour")
+}
+
+#[test]
+fn resolve_applies_armor_by_damage_type() {
+    let mut player = Player::new(0, 0, Difficulty::Normal);
+    player.armor = 4;
+
+    let blow = Hit::new(DamageSource::Player, DamageType::Physical, 10);
+    assert_eq!(damage::resolve(&mut player, &blow), 6);
+    let poison = Hit::new(DamageSource::Poison, DamageType::Poison, 3);
+    assert_eq!(damage::resolve(&mut player, &poison), 3);
+    assert_eq!(player.hp, 100 - 9);
+
+    // Caps and minimums on top of the armor
+    player.armor = 30;
+    assert_eq!(damage::resolve(&mut player, &blow), 1);
+    let capped = blow.clone().with_modifiers(DamageModifiers {
+        armor_cap: Some(5),
+        minimum: 2,
+        ..Default::default()
+    });
+    assert_eq!(damage::resolve(&mut player, &capped), 5);
+}
+
+#[test]
+fn armor_blunts_monster_blows() {
+    for (armor, expected) in [(0, 12), (5, 7), (50, 1)] {
+        let mut game = armored(armor);
+        let mut monster = Game::with_seed(Difficulty::Normal, 4).monsters.remove(0);
+        let (_, (x, y)) = open_step(&game);
+        (monster.x, monster.y) = (x, y);
+        monster.damage = 12;
+        monster.cd = 0.;
+        game.monsters.push(monster);
+
+        let hp = game.player.hp;
+        assert!(game.step(&[], SIM_DT).player_hit);
+        assert_eq!(hp - game.player.hp, expected);
+    }
+}
+
+#[test]
+fn armor_blunts_spikes_only_so_far() {
+    for (armor, expected) in [(0, 15), (4, 11), (30, 5)] {
+        let mut game = armored(armor);
+        let ((dx, dy), to) = open_step(&game);
+        game.spikes = vec![to];
+
+        let hp = game.player.hp;
+        game.step(&[PlayerCommand::Move { dx, dy }], SIM_DT);
+        assert_eq!((game.player.x, game.player.y), to);
+        assert_eq!(hp - game.player.hp, expected);
+    }
+}
+
+#[test]
+fn poison_goes_through_armor() {
+    let lost = |armor| {
+        let mut game = armored(armor);
+        game.player.poisoned = 5.;
+        let hp = game.player.hp;
+        for _ in 0..(3. / SIM_DT) as usize {
+            game.step(&[], SIM_DT);
+        }
+        hp - game.player.hp
+    };
+    assert!(lost(0) > 0);
+    assert_eq!(lost(50), lost(0));
+}
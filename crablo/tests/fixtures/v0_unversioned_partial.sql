@@ -0,0 +1,22 @@
+-- Unversioned database from a build that already added seeds, replays and
+-- snapshots by probing columns, before user_version was tracked.
+CREATE TABLE scores (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    seed INTEGER,
+    replay TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE saves (
+    id INTEGER PRIMARY KEY,
+    floor INTEGER NOT NULL,
+    hp INTEGER NOT NULL,
+    max_hp INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    snapshot TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO scores (name, score, seed, replay) VALUES ('ada', 1200, 3735928559, '{}'), ('bob', 800, NULL, NULL);
+INSERT INTO saves (floor, hp, max_hp, score, difficulty, snapshot) VALUES (4, 60, 100, 900, 1, '{}');
@@ -0,0 +1,19 @@
+-- Schema version 1: the original tables.
+CREATE TABLE scores (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE saves (
+    id INTEGER PRIMARY KEY,
+    floor INTEGER NOT NULL,
+    hp INTEGER NOT NULL,
+    max_hp INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO scores (name, score) VALUES ('ada', 1200), ('bob', 800);
+INSERT INTO saves (floor, hp, max_hp, score, difficulty) VALUES (4, 60, 100, 900, 1);
+PRAGMA user_version = 1;
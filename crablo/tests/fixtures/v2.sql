@@ -0,0 +1,22 @@
+-- Schema version 2: seeds and replays on scores, snapshots on saves.
+CREATE TABLE scores (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    seed INTEGER,
+    replay TEXT
+);
+CREATE TABLE saves (
+    id INTEGER PRIMARY KEY,
+    floor INTEGER NOT NULL,
+    hp INTEGER NOT NULL,
+    max_hp INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    snapshot TEXT
+);
+INSERT INTO scores (name, score, seed, replay) VALUES ('ada', 1200, 3735928559, '{}'), ('bob', 800, NULL, NULL);
+INSERT INTO saves (floor, hp, max_hp, score, difficulty, snapshot) VALUES (4, 60, 100, 900, 1, '{}');
+PRAGMA user_version = 2;
@@ -0,0 +1,23 @@
+-- Schema version 3: gold wallet on saves.
+CREATE TABLE scores (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    seed INTEGER,
+    replay TEXT
+);
+CREATE TABLE saves (
+    id INTEGER PRIMARY KEY,
+    floor INTEGER NOT NULL,
+    hp INTEGER NOT NULL,
+    max_hp INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    snapshot TEXT,
+    gold INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO scores (name, score, seed, replay) VALUES ('ada', 1200, 3735928559, '{}'), ('bob', 800, NULL, NULL);
+INSERT INTO saves (floor, hp, max_hp, score, gold, difficulty, snapshot) VALUES (4, 60, 100, 900, 250, 1, '{}');
+PRAGMA user_version = 3;
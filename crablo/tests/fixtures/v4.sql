@@ -0,0 +1,32 @@
+-- Schema version 4: per-run stats on scores, a single save.
+CREATE TABLE scores (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    seed INTEGER,
+    replay TEXT,
+    won INTEGER NOT NULL DEFAULT 0,
+    difficulty INTEGER,
+    floor INTEGER NOT NULL DEFAULT 0,
+    level INTEGER NOT NULL DEFAULT 0,
+    kills INTEGER NOT NULL DEFAULT 0,
+    gold INTEGER NOT NULL DEFAULT 0,
+    damage_dealt INTEGER NOT NULL DEFAULT 0,
+    duration INTEGER NOT NULL DEFAULT 0,
+    cause TEXT
+);
+CREATE TABLE saves (
+    id INTEGER PRIMARY KEY,
+    floor INTEGER NOT NULL,
+    hp INTEGER NOT NULL,
+    max_hp INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    snapshot TEXT,
+    gold INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO scores (name, score, seed, replay, floor, kills) VALUES ('ada', 1200, 3735928559, '{}', 6, 40), ('bob', 800, NULL, NULL, 3, 12);
+INSERT INTO saves (floor, hp, max_hp, score, gold, difficulty, snapshot) VALUES (9, 60, 100, 2100, 340, 2, '{}');
+PRAGMA user_version = 4;
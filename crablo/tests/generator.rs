@@ -0,0 +1,156 @@
+//! Every generator lays out walkable, fully connected floors, and rooms are
+//! furnished by their roles.
+
+use crablo::core::constants::{BOSS_FLOOR_INTERVAL, SHOP_FLOOR_INTERVAL};
+use crablo::core::rng::Rng;
+use crablo::systems::pathfinding::distances;
+use crablo::world::generator::{generate_floor, is_connected, Biome};
+use crablo::world::map::{floor_size, Map, RoomRole, Tile};
+
+#[test]
+fn every_generator_connects_its_floors() {
+    let sizes = [floor_size(1), floor_size(5), floor_size(12), floor_size(30)];
+    for biome in Biome::ALL {
+        let generator = biome.generator();
+        for &(width, height) in &sizes {
+            for seed in 0..25 {
+                let plan = generator.generate(&mut Rng::for_floor(seed, 1), width, height);
+                let map = &plan.map;
+                let name = generator.name();
+                assert_eq!((map.width(), map.height()), (width, height), "{name}");
+                assert!(is_connected(map, plan.spawn), "{name}, seed {seed}");
+                assert!(plan.rooms.len() >= 2, "{name}, seed {seed}");
+                // The outer wall is never dug through
+                assert!((0..width).all(|x| !map.is_floor(x, 0) && !map.is_floor(x, height - 1)));
+                assert!((0..height).all(|y| !map.is_floor(0, y) && !map.is_floor(width - 1, y)));
+            }
+        }
+    }
+}
+
+#[test]
+fn rooms_are_furnished_by_role() {
+    for seed in 0..20 {
+        for floor in 1..=25 {
+            let plan = generate_floor(&mut Rng::for_floor(seed, floor), floor);
+            let roles: Vec<_> = plan.rooms.iter().map(|room| room.role).collect();
+            assert_eq!(roles[0], RoomRole::Start);
+            assert!(plan.rooms[0].tiles().any(|pos| pos == plan.spawn));
+            assert!(!plan.monsters.is_empty(), "seed {seed}, floor {floor}");
+
+            let count = |role| roles.iter().filter(|&&r| r == role).count();
+            assert_eq!(
+                count(RoomRole::Boss),
+                (floor % BOSS_FLOOR_INTERVAL == 0) as usize
+            );
+            assert_eq!(
+                count(RoomRole::Shop),
+                (floor % SHOP_FLOOR_INTERVAL == 0) as usize
+            );
+            if let Some(shop) = plan.rooms.iter().find(|room| room.role == RoomRole::Shop) {
+                let (x, y) = shop.center();
+                assert_eq!(plan.map.get(x, y), Some(Tile::Floor));
+                assert_ne!((x, y), plan.spawn);
+            }
+            if let Some(boss) = plan.rooms.iter().find(|room| room.role == RoomRole::Boss) {
+                assert!(boss.tiles().any(|pos| pos == plan.monsters[0]));
+            }
+
+            let placed: Vec<_> = [
+                &plan.gold,
+                &plan.potions,
+                &plan.monsters,
+                &plan.spikes,
+                &plan.poison,
+            ]
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect();
+            for (i, &(x, y)) in placed.iter().enumerate() {
+                assert!(plan.map.is_floor(x, y));
+                assert_ne!((x, y), plan.spawn);
+                assert!(!placed[i + 1..].contains(&(x, y)), "two things on one tile");
+            }
+
+            // Safe rooms hold nothing that hurts, unless another room
+            // overlaps them
+            for room in &plan.rooms {
+                let safe = matches!(
+                    room.role,
+                    RoomRole::Start | RoomRole::Shrine | RoomRole::Shop
+                );
+                let alone = plan
+                    .rooms
+                    .iter()
+                    .filter(|other| other.intersects(room))
+                    .count()
+                    == 1;
+                if safe && alone {
+                    assert!(!plan
+                        .monsters
+                        .iter()
+                        .chain(&plan.spikes)
+                        .chain(&plan.poison)
+                        .any(|pos| room.tiles().any(|tile| tile == *pos)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn every_shop_floor_has_a_reachable_stall() {
+    for seed in 0..200 {
+        for floor in (SHOP_FLOOR_INTERVAL..=30).step_by(SHOP_FLOOR_INTERVAL as usize) {
+            let plan = generate_floor(&mut Rng::for_floor(seed, floor), floor);
+            let shops: Vec<_> = plan
+                .rooms
+                .iter()
+                .filter(|room| room.role == RoomRole::Shop)
+                .collect();
+            assert_eq!(shops.len(), 1, "seed {seed}, floor {floor}");
+
+            let (x, y) = shops[0].center();
+            assert_eq!(plan.map.get(x, y), Some(Tile::Floor));
+            assert_ne!((x, y), plan.spawn);
+            assert!(distances(&plan.map, plan.spawn)
+                .get(x, y)
+                .flatten()
+                .is_some());
+            assert!(!plan
+                .gold
+                .iter()
+                .chain(&plan.potions)
+                .chain(&plan.monsters)
+                .chain(&plan.spikes)
+                .chain(&plan.poison)
+                .any(|&pos| pos == (x, y)));
+        }
+    }
+}
+
+#[test]
+fn biomes_change_after_boss_floors() {
+    assert_eq!(Biome::for_floor(1), Biome::Halls);
+    assert_eq!(Biome::for_floor(5), Biome::Halls);
+    assert_eq!(Biome::for_floor(6), Biome::Crypts);
+    assert_eq!(Biome::for_floor(16), Biome::Tunnels);
+    assert_eq!(Biome::for_floor(21), Biome::Halls);
+
+    let first = generate_floor(&mut Rng::for_floor(3, 11), 11);
+    let again = generate_floor(&mut Rng::for_floor(3, 11), 11);
+    assert_eq!(first.map, again.map);
+    assert_eq!(first.spawn, again.spawn);
+}
+
+#[test]
+fn split_floors_are_not_connected() {
+    let mut map = Map::new(5, 3, Tile::Wall);
+    map.set(1, 1, Tile::Floor);
+    map.set(3, 1, Tile::Floor);
+    assert!(!is_connected(&map, (1, 1)));
+    map.set(2, 1, Tile::Floor);
+    assert!(is_connected(&map, (1, 1)));
+    assert!(!is_connected(&map, (0, 0)));
+}
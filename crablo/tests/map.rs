@@ -0,0 +1,58 @@
+//! Maps are sized per floor, stay in bounds and load from old snapshots.
+
+use crablo::core::rng::Rng;
+use crablo::world::generator::generate_floor;
+use crablo::world::map::{floor_size, Map, Tile};
+
+#[test]
+fn accessors_are_bounds_checked() {
+    let mut map = Map::new(3, 2, Tile::Wall);
+    map.set(2, 1, Tile::Floor);
+    map.set(3, 0, Tile::Floor);
+
+    assert_eq!(map.get(2, 1), Some(Tile::Floor));
+    assert_eq!(map.get(3, 0), None);
+    assert!(!map.is_floor(0, 5));
+    assert_eq!(map.step((0, 0), (-1, 0)), None);
+    assert_eq!(map.step((0, 0), (1, 1)), Some((1, 1)));
+}
+
+#[test]
+fn neighbours_stay_on_the_map() {
+    let map = Map::new(4, 4, Tile::Floor);
+    assert_eq!(map.neighbours((0, 0)).count(), 3);
+    assert_eq!(map.neighbours((1, 1)).count(), 8);
+    assert_eq!(map.around((0, 0), 1).count(), 4);
+}
+
+#[test]
+fn floors_grow_with_depth() {
+    assert_eq!(floor_size(1), (20, 20));
+    let (w4, h4) = floor_size(4);
+    assert!(w4 > 20 && h4 > 20);
+    // Boss arenas are wider than they are tall
+    let (w, h) = floor_size(5);
+    assert!(w > h);
+
+    for floor in 1..=30 {
+        let plan = generate_floor(&mut Rng::for_floor(7, floor), floor);
+        let map = &plan.map;
+        assert_eq!((map.width(), map.height()), floor_size(floor));
+        assert!(map.is_floor(plan.spawn.0, plan.spawn.1));
+    }
+}
+
+#[test]
+fn maps_round_trip_and_reject_mismatched_cells() {
+    let mut map = Map::new(2, 3, Tile::Wall);
+    map.set(1, 2, Tile::Floor);
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!(serde_json::from_str::<Map>(&json).unwrap(), map);
+
+    // A corrupted snapshot can't produce a map its accessors would overrun
+    let short = r#"{"width":2,"height":2,"cells":[true,false,true]}"#;
+    assert!(serde_json::from_str::<Map<bool>>(short).is_err());
+    let huge = format!(r#"{{"width":{},"height":2,"cells":[]}}"#, usize::MAX);
+    assert!(serde_json::from_str::<Map<bool>>(&huge).is_err());
+    assert!(serde_json::from_str::<Map<bool>>("[[true,false],[false,false]]").is_err());
+}
@@ -0,0 +1,200 @@
+//! Schema migrations, checked against database fixtures from each version.
+
+#![cfg(feature = "sqlite")]
+
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+
+use crablo::core::database::{Database, SCHEMA_VERSION};
+use crablo::core::storage::{Storage, StorageError};
+
+/// A database file in the temp dir, removed when dropped.
+struct TempDb(PathBuf);
+
+impl TempDb {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "crablo-migrations-{}-{}.db",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        TempDb(path)
+    }
+
+    /// Create the database from a fixture in `tests/fixtures`.
+    fn from_fixture(fixture: &str) -> Self {
+        let db = TempDb::new(fixture);
+        let path = format!(
+            "{}/tests/fixtures/{}.sql",
+            env!("CARGO_MANIFEST_DIR"),
+            fixture
+        );
+        let sql = std::fs::read_to_string(path).expect("fixture exists");
+        Connection::open(&db.0)
+            .and_then(|conn| conn.execute_batch(&sql))
+            .expect("fixture loads");
+        db
+    }
+
+    fn connection(&self) -> Connection {
+        Connection::open(&self.0).unwrap()
+    }
+
+    fn user_version(&self) -> i32 {
+        self.connection()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn columns(&self, table: &str) -> Vec<String> {
+        let conn = self.connection();
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({})", table))
+            .unwrap();
+        let names = stmt.query_map([], |row| row.get(1)).unwrap();
+        names.map(|name| name.unwrap()).collect()
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Open the fixture and check it lands on the current schema with its scores
+/// intact.
+fn upgrade(fixture: &str) -> (TempDb, Database) {
+    let file = TempDb::from_fixture(fixture);
+    let db = Database::open(&file.0).expect("migrates");
+    assert_eq!(file.user_version(), SCHEMA_VERSION);
+
+    for column in [
+        "seed",
+        "replay",
+        "won",
+        "difficulty",
+        "kills",
+        "duration",
+        "cause",
+    ] {
+        assert!(
+            file.columns("scores").iter().any(|c| c == column),
+            "{}",
+            column
+        );
+    }
+    for column in ["snapshot", "gold", "slot", "name", "level", "playtime"] {
+        assert!(
+            file.columns("saves").iter().any(|c| c == column),
+            "{}",
+            column
+        );
+    }
+
+    assert_eq!(file.columns("meta"), ["key", "value"]);
+
+    let scores = db.get_top_scores(10, None).unwrap();
+    let names: Vec<_> = scores.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["ada", "bob"]);
+    assert_eq!(scores[0].score, 1200);
+    assert!(!scores[0].stats.won);
+    assert!(scores[0].stats.difficulty.is_none());
+    (file, db)
+}
+
+#[test]
+fn fresh_database_gets_current_schema() {
+    let file = TempDb::new("fresh");
+    let db = Database::open(&file.0).unwrap();
+    assert_eq!(file.user_version(), SCHEMA_VERSION);
+    assert!(db.get_top_scores(10, None).unwrap().is_empty());
+    assert!(!db.has_save().unwrap());
+}
+
+#[test]
+fn upgrades_unversioned_baseline() {
+    let (_file, db) = upgrade("v0_baseline");
+    // Saves from before snapshots cannot be resumed
+    assert!(db.load_game(1).unwrap().is_none());
+}
+
+#[test]
+fn upgrades_unversioned_database_with_later_columns() {
+    let (_file, db) = upgrade("v0_unversioned_partial");
+    let scores = db.get_top_scores(10, None).unwrap();
+    assert_eq!(scores[0].seed, Some(0xDEADBEEF));
+    assert!(scores[0].has_replay);
+    assert_eq!(db.load_game(1).unwrap().unwrap().gold, 0);
+}
+
+#[test]
+fn upgrades_from_v1() {
+    let (_file, db) = upgrade("v1");
+    assert!(db.load_game(1).unwrap().is_none());
+}
+
+#[test]
+fn upgrades_from_v2() {
+    let (_file, db) = upgrade("v2");
+    let save = db.load_game(1).unwrap().unwrap();
+    assert_eq!((save.floor, save.gold), (4, 0));
+}
+
+#[test]
+fn upgrades_from_v3() {
+    let (_file, db) = upgrade("v3");
+    assert_eq!(db.load_game(1).unwrap().unwrap().gold, 250);
+}
+
+#[test]
+fn upgrades_from_v4() {
+    let (_file, db) = upgrade("v4");
+    // The single save becomes slot 1
+    let slots = db.list_saves().unwrap();
+    assert_eq!(slots.len(), 1);
+    assert_eq!((slots[0].slot, slots[0].floor, slots[0].gold), (1, 9, 340));
+    assert!(slots[0].name.is_empty());
+    assert_eq!(db.load_game(1).unwrap().unwrap().gold, 340);
+}
+
+#[test]
+fn reopening_current_schema_is_a_no_op() {
+    let (file, db) = upgrade("v3");
+    drop(db);
+    let db = Database::open(&file.0).unwrap();
+    assert_eq!(file.user_version(), SCHEMA_VERSION);
+    assert_eq!(db.get_top_scores(10, None).unwrap().len(), 2);
+}
+
+#[test]
+fn refuses_newer_schema() {
+    let file = TempDb::from_fixture("v3");
+    file.connection()
+        .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+
+    match Database::open(&file.0) {
+        Err(StorageError::NewerVersion { found, supported }) => {
+            assert_eq!((found, supported), (SCHEMA_VERSION + 1, SCHEMA_VERSION));
+        }
+        _ => panic!("newer schema was opened"),
+    }
+    assert_eq!(file.user_version(), SCHEMA_VERSION + 1);
+}
+
+#[test]
+fn failed_migration_rolls_back() {
+    // A v1 database missing its saves table cannot take the v2 columns
+    let file = TempDb::from_fixture("v1");
+    file.connection().execute("DROP TABLE saves", []).unwrap();
+
+    assert!(matches!(
+        Database::open(&file.0),
+        Err(StorageError::Sqlite(_))
+    ));
+    assert_eq!(file.user_version(), 1);
+    assert!(!file.columns("scores").iter().any(|c| c == "seed"));
+}
@@ -0,0 +1,30 @@
+//! Replays remember the monster roster they were recorded with.
+
+use crablo::core::constants::Difficulty;
+use crablo::core::replay::Replay;
+use crablo::world::bestiary::bestiary;
+
+#[test]
+fn replays_only_match_the_roster_they_were_recorded_with() {
+    let replay = Replay::new(7, Difficulty::Normal);
+    assert_eq!(replay.roster, Some(bestiary().fingerprint()));
+    assert!(replay.matches_roster());
+
+    let json = replay.to_json().unwrap();
+    assert!(Replay::from_json(&json).unwrap().matches_roster());
+
+    let mut tuned = bestiary().clone();
+    tuned.monsters[0].hp += 1;
+    assert_ne!(tuned.fingerprint(), bestiary().fingerprint());
+    let other = Replay {
+        roster: Some(tuned.fingerprint()),
+        ..replay.clone()
+    };
+    assert!(!other.matches_roster());
+
+    // Replays from before rosters were tracked still play
+    let old = json.replace(&format!(",\"roster\":{}", replay.roster.unwrap()), "");
+    assert!(!old.contains("roster"));
+    assert_eq!(Replay::from_json(&old).unwrap().roster, None);
+    assert!(Replay::from_json(&old).unwrap().matches_roster());
+}
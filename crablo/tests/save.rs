@@ -0,0 +1,53 @@
+//! Saved snapshots resume a run exactly where it was left.
+
+use crablo::core::constants::Difficulty;
+use crablo::core::Game;
+use crablo::world::entities::EquipmentType;
+
+#[test]
+fn saves_keep_progress_fog_monsters_and_traps() {
+    let mut game = Game::with_difficulty(Difficulty::Hard);
+    game.floor = 3;
+    game.player.level = 4;
+    game.player.xp = 37;
+    game.player.xp_to_next = 250;
+    game.player.weapon_damage = 10;
+    game.player.armor = 5;
+    game.player.max_hp += 20;
+    game.player.hp = 42;
+    game.player.poisoned = 2.5;
+    game.equipment_drops
+        .push((game.player.x, game.player.y, EquipmentType::Ring));
+    game.explored.set(1, 1, true);
+    game.monsters[0].hp -= 3;
+    game.spikes.push((2, 3));
+    game.poison.push((3, 2));
+
+    let save = game.to_save().unwrap();
+    assert_eq!((save.floor, save.hp), (3, 42));
+    let resumed = Game::from_save(&save).unwrap();
+    assert_eq!(resumed.floor, 3);
+    assert_eq!(resumed.player.hp, 42);
+    assert_eq!(resumed.player.max_hp, game.player.max_hp);
+    assert_eq!(resumed.player.level, 4);
+    assert_eq!(resumed.player.xp, 37);
+    assert_eq!(resumed.player.xp_to_next, 250);
+    assert_eq!(resumed.player.weapon_damage, 10);
+    assert_eq!(resumed.player.armor, 5);
+    assert_eq!(resumed.player.poisoned, 2.5);
+    assert!(resumed.equipment_drops == game.equipment_drops);
+    assert_eq!(resumed.explored, game.explored);
+    assert!(resumed.map == game.map);
+    assert_eq!(resumed.monsters.len(), game.monsters.len());
+    for (resumed, monster) in resumed.monsters.iter().zip(&game.monsters) {
+        assert_eq!((resumed.x, resumed.y), (monster.x, monster.y));
+        assert_eq!(resumed.hp, monster.hp);
+        assert_eq!(resumed.kind, monster.kind);
+    }
+    assert_eq!(resumed.gold, game.gold);
+    assert_eq!(resumed.potions, game.potions);
+    assert_eq!(resumed.spikes, game.spikes);
+    assert_eq!(resumed.poison, game.poison);
+    // Nothing else is lost either
+    assert_eq!(resumed.to_save().unwrap().snapshot, save.snapshot);
+}
@@ -0,0 +1,143 @@
+//! Settings and key bindings survive a round trip to disk and tolerate bad
+//! files.
+
+use std::path::PathBuf;
+
+use crablo::core::bindings::{Action, Bindings};
+use crablo::core::settings::{Palette, Settings};
+use macroquad::input::KeyCode;
+
+/// A path in the temp dir, removed when dropped.
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("crablo-settings-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        TempPath(path)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn saved_settings_load_back() {
+    let path = TempPath::new("round-trip.toml");
+    let settings = Settings {
+        master_volume: 0.3,
+        fullscreen: true,
+        vsync: false,
+        ui_scale: 1.5,
+        screen_shake: 0.,
+        palette: Palette::ColorBlind,
+        ..Default::default()
+    };
+    settings.save(&path.0).unwrap();
+
+    assert_eq!(Settings::load(&path.0).unwrap(), settings);
+}
+
+#[test]
+fn missing_keys_use_defaults_and_values_are_clamped() {
+    let settings = Settings::from_toml("master_volume = 3.0\nui_scale = 0.1\n").unwrap();
+
+    assert_eq!(settings.master_volume, 1.);
+    assert_eq!(settings.ui_scale, 0.5);
+    assert_eq!(settings.palette, Palette::Classic);
+    assert!(settings.vsync);
+
+    // Keys the game no longer has are ignored
+    let old = Settings::from_toml("music_volume = 0.7\nsfx_volume = 0.5\n").unwrap();
+    assert_eq!(old.sfx_volume, 0.5);
+}
+
+#[test]
+fn unreadable_file_is_reported_and_missing_one_is_not() {
+    let path = TempPath::new("broken.toml");
+    std::fs::write(&path.0, "palette = \"sepia\"").unwrap();
+    let error = Settings::load(&path.0).unwrap_err();
+    assert!(error.contains("broken.toml"), "{}", error);
+
+    // Not having saved any settings yet is fine
+    let missing = TempPath::new("missing.toml");
+    assert_eq!(Settings::load(&missing.0).unwrap(), Settings::default());
+}
+
+#[test]
+fn default_bindings_have_no_conflicts() {
+    let bindings = Bindings::default();
+    let mut seen = vec![];
+    for action in Action::ALL {
+        let keys: Vec<_> = bindings.keys(action).into_iter().flatten().collect();
+        assert!(!keys.is_empty(), "{:?} has no key", action);
+        for key in keys {
+            assert!(!seen.contains(&key), "{:?} is bound twice", key);
+            seen.push(key);
+        }
+    }
+}
+
+#[test]
+fn binding_a_used_key_takes_it_from_the_other_action() {
+    let mut bindings = Bindings::default();
+    bindings.set(Action::Dash, 1, Some(KeyCode::Q));
+
+    assert_eq!(bindings.action_for(KeyCode::Q), Some((Action::Dash, 1)));
+    assert_eq!(bindings.keys(Action::AreaAttack), [None, None]);
+}
+
+#[test]
+fn swapping_never_leaves_an_action_without_keys() {
+    let mut bindings = Bindings::default();
+    // Pause keeps P when Escape moves to an empty slot
+    assert_eq!(bindings.swap(Action::Dash, 1, KeyCode::Escape), Ok(()));
+    assert_eq!(bindings.keys(Action::Pause), [Some(KeyCode::P), None]);
+
+    // Taking P as well would leave Pause with nothing
+    let before = bindings.clone();
+    assert_eq!(
+        bindings.swap(Action::Heal, 1, KeyCode::P),
+        Err(Action::Pause)
+    );
+    assert_eq!(bindings, before);
+
+    // A filled slot trades its key for the one taken
+    assert_eq!(bindings.swap(Action::Heal, 0, KeyCode::P), Ok(()));
+    assert_eq!(bindings.keys(Action::Heal), [Some(KeyCode::P), None]);
+    assert_eq!(bindings.keys(Action::Pause), [Some(KeyCode::E), None]);
+}
+
+#[test]
+fn rebound_keys_are_saved_by_name() {
+    let path = TempPath::new("bindings.toml");
+    let mut settings = Settings::default();
+    settings.bindings.set(Action::MoveUp, 0, Some(KeyCode::Z));
+    settings
+        .bindings
+        .set(Action::AreaAttack, 0, Some(KeyCode::X));
+    settings.save(&path.0).unwrap();
+
+    let text = std::fs::read_to_string(&path.0).unwrap();
+    assert!(text.contains("move_up = [\"Z\", \"Up\"]"), "{}", text);
+    assert_eq!(Settings::load(&path.0).unwrap(), settings);
+}
+
+#[test]
+fn bad_bindings_in_the_file_are_dropped() {
+    let settings = Settings::from_toml(
+        "[bindings]\ndash = [\"Q\", \"NoSuchKey\"]\narea_attack = [\"Q\"]\nfly = [\"F\"]\n",
+    )
+    .unwrap();
+    let bindings = &settings.bindings;
+
+    assert_eq!(bindings.keys(Action::Dash), [Some(KeyCode::Q), None]);
+    // Q went to the action listed first
+    assert_eq!(bindings.keys(Action::AreaAttack), [None, None]);
+    // Actions missing from the file keep their defaults
+    assert_eq!(bindings.keys(Action::Heal), [Some(KeyCode::E), None]);
+}
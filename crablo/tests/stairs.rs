@@ -0,0 +1,71 @@
+//! Stairs go far from the spawn, and only taking them ends a floor.
+
+use crablo::core::command::PlayerCommand;
+use crablo::core::constants::{Difficulty, BOSS_FLOOR_INTERVAL, SIM_DT};
+use crablo::core::rng::Rng;
+use crablo::core::Game;
+use crablo::systems::pathfinding::distances;
+use crablo::world::generator::generate_floor;
+use crablo::world::map::Tile;
+
+#[test]
+fn stairs_are_the_longest_walk_from_the_spawn() {
+    for seed in 0..10 {
+        for floor in 1..=20 {
+            let plan = generate_floor(&mut Rng::for_floor(seed, floor), floor);
+            let map = &plan.map;
+            let stairs = map.stairs().expect("every floor has stairs");
+            let count = map
+                .positions()
+                .filter(|&(x, y)| map.get(x, y) == Some(Tile::StairsDown))
+                .count();
+            assert_eq!(count, 1);
+
+            let distance = distances(map, plan.spawn);
+            let farthest = map
+                .positions()
+                .filter_map(|(x, y)| distance.get(x, y).flatten())
+                .max();
+            assert_eq!(distance.get(stairs.0, stairs.1).flatten(), farthest);
+            assert!(!plan
+                .gold
+                .iter()
+                .chain(&plan.potions)
+                .chain(&plan.monsters)
+                .chain(&plan.spikes)
+                .chain(&plan.poison)
+                .any(|&pos| pos == stairs));
+        }
+    }
+}
+
+#[test]
+fn floors_end_only_by_taking_open_stairs() {
+    let mut game = Game::with_seed(Difficulty::Normal, 9);
+    let stairs = game.map.stairs().unwrap();
+
+    // Clearing the floor no longer moves on by itself
+    game.monsters.clear();
+    game.step(&[], SIM_DT);
+    assert_eq!(game.floor, 1);
+
+    // Descending only works on the stairs
+    game.step(&[PlayerCommand::Descend], SIM_DT);
+    assert_eq!(game.floor, 1);
+    (game.player.x, game.player.y) = stairs;
+    assert!(game.step(&[PlayerCommand::Descend], SIM_DT).floor_completed);
+    assert_eq!(game.floor, 2);
+
+    // A living boss seals them
+    while game.floor % BOSS_FLOOR_INTERVAL != 0 {
+        game.next_floor();
+    }
+    assert!(game.monsters.iter().any(|m| m.boss));
+    (game.player.x, game.player.y) = game.map.stairs().unwrap();
+    let floor = game.floor;
+    game.step(&[PlayerCommand::Descend], SIM_DT);
+    assert_eq!(game.floor, floor);
+    game.monsters.retain(|m| !m.boss);
+    game.step(&[PlayerCommand::Descend], SIM_DT);
+    assert_eq!(game.floor, floor + 1);
+}
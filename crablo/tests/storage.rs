@@ -0,0 +1,236 @@
+//! The same score and save behaviour from every storage backend.
+
+use std::path::PathBuf;
+
+use crablo::core::constants::{Difficulty, SIM_DT};
+use crablo::core::storage::{
+    interrupted_run, Backend, JsonStorage, MemoryStorage, RunStats, Storage, AUTOSAVE_SLOT,
+};
+use crablo::core::Game;
+
+/// A path in the temp dir, removed when dropped.
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("crablo-storage-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        TempPath(path)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn stats(difficulty: Difficulty, won: bool) -> RunStats {
+    RunStats {
+        difficulty: Some(difficulty),
+        floor: 7,
+        won,
+        ..Default::default()
+    }
+}
+
+/// Exercise every trait method against a fresh backend.
+fn check_contract(storage: &mut dyn Storage) {
+    assert!(storage.get_top_scores(10, None).unwrap().is_empty());
+    assert!(!storage.has_save().unwrap());
+
+    storage
+        .save_score(
+            "ada",
+            500,
+            1,
+            "{\"frames\":1}",
+            &stats(Difficulty::Hard, true),
+        )
+        .unwrap();
+    storage
+        .save_score("bob", 900, 2, "", &stats(Difficulty::Easy, false))
+        .unwrap();
+
+    let all = storage.get_top_scores(10, None).unwrap();
+    let ranked: Vec<_> = all.iter().map(|s| (s.rank, s.name.as_str())).collect();
+    assert_eq!(ranked, [(1, "bob"), (2, "ada")]);
+    assert_eq!(all[1].seed, Some(1));
+    assert_eq!(all[1].stats.floor, 7);
+    assert!(all[1].stats.won);
+    assert_eq!(
+        storage.load_replay(all[1].id).unwrap().as_deref(),
+        Some("{\"frames\":1}")
+    );
+
+    let hard = storage.get_top_scores(10, Some(Difficulty::Hard)).unwrap();
+    assert_eq!(hard.len(), 1);
+    assert_eq!((hard[0].rank, hard[0].name.as_str()), (1, "ada"));
+    assert_eq!(storage.get_top_scores(1, None).unwrap().len(), 1);
+
+    storage.reset_scores().unwrap();
+    assert!(storage.get_top_scores(10, None).unwrap().is_empty());
+
+    let mut save = Game::with_seed(Difficulty::Normal, 42).to_save().unwrap();
+    save.name = "first".to_string();
+    storage.save_game(3, &save).unwrap();
+    save.name = "second".to_string();
+    storage.save_game(1, &save).unwrap();
+    assert!(storage.has_save().unwrap());
+    assert!(storage.load_game(2).unwrap().is_none());
+    let loaded = storage.load_game(3).unwrap().unwrap();
+    assert_eq!(loaded.snapshot, save.snapshot);
+    assert_eq!(loaded.name, "first");
+
+    let slots = storage.list_saves().unwrap();
+    let listed: Vec<_> = slots.iter().map(|s| (s.slot, s.name.as_str())).collect();
+    assert_eq!(listed, [(1, "second"), (3, "first")]);
+    assert_eq!(slots[0].floor, save.floor);
+    assert!(matches!(slots[0].difficulty, Some(Difficulty::Normal)));
+
+    // Saving over a slot replaces it
+    save.name = "third".to_string();
+    storage.save_game(3, &save).unwrap();
+    assert_eq!(storage.load_game(3).unwrap().unwrap().name, "third");
+    assert_eq!(storage.list_saves().unwrap().len(), 2);
+
+    storage.delete_save(1).unwrap();
+    storage.delete_save(3).unwrap();
+    assert!(!storage.has_save().unwrap());
+    assert!(storage.load_game(3).unwrap().is_none());
+
+    assert!(!storage.begin_session().unwrap());
+    assert!(storage.begin_session().unwrap());
+    storage.end_session().unwrap();
+    assert!(!storage.begin_session().unwrap());
+}
+
+#[test]
+fn backends_parse_by_name_and_reject_unknown_ones() {
+    assert!(matches!(Backend::parse("json"), Ok(Backend::Json)));
+    assert!(matches!(Backend::parse("Memory"), Ok(Backend::Memory)));
+    assert_eq!(
+        Backend::parse("jsn").err().unwrap(),
+        "unknown storage backend 'jsn'"
+    );
+}
+
+#[test]
+fn memory_backend() {
+    check_contract(&mut MemoryStorage::new());
+}
+
+#[test]
+fn json_backend() {
+    let file = TempPath::new("contract.json");
+    check_contract(&mut JsonStorage::open(&file.0).unwrap());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_backend() {
+    let file = TempPath::new("contract.db");
+    check_contract(&mut *Backend::Sqlite.open(&file.0).unwrap());
+}
+
+#[test]
+fn json_backend_persists_across_opens() {
+    let file = TempPath::new("reopen.json");
+    {
+        let mut storage = Backend::Json.open(&file.0).unwrap();
+        storage
+            .save_score("ada", 500, 1, "", &stats(Difficulty::Normal, false))
+            .unwrap();
+    }
+    let storage = JsonStorage::open(&file.0).unwrap();
+    assert_eq!(storage.get_top_scores(10, None).unwrap()[0].name, "ada");
+}
+
+#[test]
+fn json_backend_detects_interrupted_session() {
+    let file = TempPath::new("session.json");
+    {
+        // Dropped without ending the session, as in a crash
+        let mut storage = JsonStorage::open(&file.0).unwrap();
+        assert!(!storage.begin_session().unwrap());
+    }
+    let mut storage = JsonStorage::open(&file.0).unwrap();
+    assert!(storage.begin_session().unwrap());
+    storage.end_session().unwrap();
+
+    let mut storage = JsonStorage::open(&file.0).unwrap();
+    assert!(!storage.begin_session().unwrap());
+}
+
+#[test]
+fn autosave_is_offered_only_after_a_crash() {
+    let mut storage = MemoryStorage::new();
+    assert!(interrupted_run(&mut storage).is_none());
+
+    // A crash with nothing autosaved leaves nothing to resume
+    assert!(interrupted_run(&mut storage).is_none());
+
+    let mut save = Game::with_seed(Difficulty::Normal, 5).to_save().unwrap();
+    save.name = "Autosave".to_string();
+    storage.save_game(AUTOSAVE_SLOT, &save).unwrap();
+    storage.save_game(2, &save).unwrap();
+    let run = interrupted_run(&mut storage).expect("the crashed run is offered");
+    assert_eq!(run.slot, AUTOSAVE_SLOT);
+
+    // A clean exit keeps the autosave but doesn't offer it
+    storage.end_session().unwrap();
+    assert!(interrupted_run(&mut storage).is_none());
+    assert!(storage.load_game(AUTOSAVE_SLOT).unwrap().is_some());
+}
+
+#[test]
+fn json_autosave_survives_a_crash() {
+    let file = TempPath::new("crash.json");
+    {
+        let mut storage = JsonStorage::open(&file.0).unwrap();
+        assert!(interrupted_run(&mut storage).is_none());
+        let save = Game::with_seed(Difficulty::Easy, 8).to_save().unwrap();
+        storage.save_game(AUTOSAVE_SLOT, &save).unwrap();
+    }
+    let mut storage = JsonStorage::open(&file.0).unwrap();
+    assert!(interrupted_run(&mut storage).is_some());
+}
+
+#[test]
+fn json_single_save_moves_to_slot_one() {
+    let file = TempPath::new("v1.json");
+    let save = Game::with_seed(Difficulty::Easy, 3).to_save().unwrap();
+    let legacy = serde_json::json!({
+        "version": 1,
+        "next_id": 1,
+        "scores": [],
+        "save": save,
+    });
+    std::fs::write(&file.0, legacy.to_string()).unwrap();
+
+    let storage = JsonStorage::open(&file.0).unwrap();
+    let slots = storage.list_saves().unwrap();
+    assert_eq!(slots.len(), 1);
+    assert_eq!(slots[0].slot, 1);
+    assert_eq!(
+        storage.load_game(1).unwrap().unwrap().snapshot,
+        save.snapshot
+    );
+}
+
+#[test]
+fn saved_run_resumes_exactly() {
+    let mut storage = MemoryStorage::new();
+    let mut game = Game::with_seed(Difficulty::Normal, 7);
+    for _ in 0..120 {
+        game.step(&[], SIM_DT);
+    }
+    storage.save_game(2, &game.to_save().unwrap()).unwrap();
+
+    let save = storage.load_game(2).unwrap().unwrap();
+    let resumed = Game::from_save(&save).unwrap();
+    assert_eq!(resumed.floor, game.floor);
+    assert_eq!(resumed.player.hp, game.player.hp);
+    assert_eq!(resumed.playtime(), game.playtime());
+}
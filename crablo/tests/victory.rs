@@ -0,0 +1,37 @@
+//! Leaving the final floor of a difficulty wins the run.
+
+use crablo::core::command::PlayerCommand;
+use crablo::core::constants::{Difficulty, SIM_DT};
+use crablo::core::Game;
+
+#[test]
+fn leaving_the_final_floor_wins_the_run() {
+    for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+        let mut game = Game::with_seed(difficulty, 3);
+        while game.floor < difficulty.final_floor() {
+            (game.player.x, game.player.y) = game.map.stairs().unwrap();
+            game.monsters.retain(|m| !m.boss);
+            let events = game.step(&[PlayerCommand::Descend], SIM_DT);
+            assert!(events.floor_completed);
+            assert!(!events.victory && !events.game_over);
+        }
+        assert!(!game.won);
+
+        // The boss keeps the way out shut
+        (game.player.x, game.player.y) = game.map.stairs().unwrap();
+        assert!(game.stairs_sealed());
+        assert!(!game.step(&[PlayerCommand::Descend], SIM_DT).victory);
+
+        game.monsters.retain(|m| !m.boss);
+        let events = game.step(&[PlayerCommand::Descend], SIM_DT);
+        assert!(events.victory);
+        assert!(events.game_over);
+        assert!(game.won);
+        assert_eq!(game.floor, difficulty.final_floor());
+        assert!(game.run_stats().won);
+
+        // The run stays over
+        let events = game.step(&[], SIM_DT);
+        assert!(events.game_over);
+    }
+}